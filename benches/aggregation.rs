@@ -0,0 +1,177 @@
+//! Compares loading every ledger row and folding in Rust against aggregating in SQLite.
+//!
+//! cargo bench --bench aggregation
+//! BENCH_ROWS=500000 cargo bench --bench aggregation
+
+use std::{
+    collections::HashMap,
+    fs,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Result, anyhow};
+use chrono::{Days, NaiveDate};
+use diesel::prelude::*;
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+use rand::Rng;
+use your_money_left_the_chat::{
+    domain::{
+        entities::my_ledger::RecordMyLedgerDto,
+        repositories::{
+            spending_scanner::SpendingScannerRepository, tax_simulator::TaxSimulatorRepository,
+        },
+    },
+    infrastructure::database::{
+        conn,
+        repositories::{
+            spending_scanner::SpendingScannerSqlite, tax_simulator::TaxSimulatorSqlite,
+        },
+        schema::my_ledger,
+    },
+};
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("src/infrastructure/database/migrations");
+
+const DEFAULT_ROWS: usize = 3_000_000;
+const INSERT_CHUNK: usize = 5_000;
+const ITERATIONS: u32 = 3;
+const CATEGORIES: &[&str] = &[
+    "FOOD",
+    "COFFEE",
+    "TRANSPORT",
+    "SHOPPING",
+    "BILLS",
+    "HEALTH",
+    "TRAVEL",
+    "ENTERTAINMENT",
+];
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let rows = std::env::var("BENCH_ROWS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_ROWS);
+
+    let database_path = std::env::temp_dir().join("your_money_left_the_chat_bench.db");
+    let _ = fs::remove_file(&database_path);
+
+    let db_pool = Arc::new(conn(&database_path.to_string_lossy())?);
+    db_pool
+        .get()?
+        .run_pending_migrations(MIGRATIONS)
+        .map_err(|e| anyhow!(e))?;
+
+    let started = Instant::now();
+    seed(&mut *db_pool.get()?, rows)?;
+    println!("seeded {} rows in {:?}", rows, started.elapsed());
+
+    let spending_scanner = SpendingScannerSqlite::new(Arc::clone(&db_pool));
+    let tax_simulator = TaxSimulatorSqlite::new(Arc::clone(&db_pool));
+
+    let load_and_fold = measure(|| async {
+        let results = spending_scanner.lifetime().await?;
+        Ok(results.iter().fold(HashMap::new(), |mut acc, r| {
+            *acc.entry(r.category.clone()).or_insert(0.0) += r.amount;
+            acc
+        }))
+    })
+    .await?;
+    let group_by = measure(|| async {
+        let totals = spending_scanner
+            .sum_by_category("0001-01-01".to_string(), "9999-12-31".to_string())
+            .await?;
+        Ok(totals
+            .into_iter()
+            .map(|t| (t.category, t.total.unwrap_or(0.0)))
+            .collect::<HashMap<String, f32>>())
+    })
+    .await?;
+    report("visualize (lifetime)", load_and_fold, group_by);
+
+    let load_and_fold = measure(|| async {
+        let incomes = tax_simulator.view_all_income_by_year(2024).await?;
+        Ok(incomes.iter().fold(HashMap::new(), |mut acc, income| {
+            *acc.entry(income.income_type()).or_insert(0.0) += income.amount;
+            acc
+        }))
+    })
+    .await?;
+    let group_by = measure(|| async {
+        let totals = tax_simulator.income_by_type(2024).await?;
+        Ok(totals.iter().fold(HashMap::new(), |mut acc, total| {
+            *acc.entry(total.income_type()).or_insert(0.0) += total.total.unwrap_or(0.0);
+            acc
+        }))
+    })
+    .await?;
+    report("simulate (income by type)", load_and_fold, group_by);
+
+    let _ = fs::remove_file(&database_path);
+
+    Ok(())
+}
+
+/// Four years of entries, mostly expenses with a salary-like income every so often.
+fn seed(conn: &mut SqliteConnection, rows: usize) -> Result<()> {
+    let mut rng = rand::rng();
+    let first_day = NaiveDate::from_ymd_opt(2022, 1, 1).unwrap();
+
+    conn.transaction(|conn| {
+        for chunk_start in (0..rows).step_by(INSERT_CHUNK) {
+            let chunk = (chunk_start..rows.min(chunk_start + INSERT_CHUNK))
+                .map(|_| {
+                    let is_income = rng.random_ratio(1, 20);
+                    let category = CATEGORIES[rng.random_range(0..CATEGORIES.len())];
+
+                    RecordMyLedgerDto {
+                        amount: match is_income {
+                            true => rng.random_range(1_000.0..50_000.0),
+                            _ => -rng.random_range(20.0..2_000.0),
+                        },
+                        category: match is_income {
+                            true => "SALARY".to_string(),
+                            _ => category.to_string(),
+                        },
+                        description: format!("{} #{}", category, rng.random_range(0..500)),
+                        date: (first_day + Days::new(rng.random_range(0..1_461))).to_string(),
+                        income_type: None,
+                    }
+                })
+                .collect::<Vec<RecordMyLedgerDto>>();
+
+            diesel::insert_into(my_ledger::table)
+                .values(&chunk)
+                .execute(conn)?;
+        }
+
+        Ok(())
+    })
+}
+
+async fn measure<F, Fut, T>(f: F) -> Result<Duration>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    // Warm up the page cache so the first run does not pay for disk reads.
+    f().await?;
+
+    let started = Instant::now();
+    for _ in 0..ITERATIONS {
+        std::hint::black_box(f().await?);
+    }
+
+    Ok(started.elapsed() / ITERATIONS)
+}
+
+fn report(name: &str, load_and_fold: Duration, group_by: Duration) {
+    println!(
+        "{:<28} load + fold: {:>10.2?}   GROUP BY: {:>10.2?}   {:.1}x faster",
+        name,
+        load_and_fold,
+        group_by,
+        load_and_fold.as_secs_f64() / group_by.as_secs_f64()
+    );
+}
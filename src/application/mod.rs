@@ -1 +1,2 @@
+pub mod statistics;
 pub mod use_cases;
@@ -0,0 +1,140 @@
+pub fn mean(values: &[f32]) -> Option<f32> {
+    match values.is_empty() {
+        true => None,
+        _ => Some(values.iter().sum::<f32>() / values.len() as f32),
+    }
+}
+
+pub fn median(values: &[f32]) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f32::total_cmp);
+
+    let mid = sorted.len() / 2;
+    match sorted.len() % 2 {
+        0 => Some((sorted[mid - 1] + sorted[mid]) / 2.0),
+        _ => Some(sorted[mid]),
+    }
+}
+
+/// Linear interpolation between the closest ranks, `p` goes from 0 to 100.
+pub fn percentile(values: &[f32], p: f32) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f32::total_cmp);
+
+    let rank = (p.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f32;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f32))
+}
+
+/// Population standard deviation.
+pub fn std_dev(values: &[f32]) -> Option<f32> {
+    let center = mean(values)?;
+    let variance = values.iter().map(|v| (v - center).powi(2)).sum::<f32>() / values.len() as f32;
+
+    Some(variance.sqrt())
+}
+
+pub fn median_absolute_deviation(values: &[f32]) -> Option<f32> {
+    let center = median(values)?;
+    let deviations = values
+        .iter()
+        .map(|v| (v - center).abs())
+        .collect::<Vec<f32>>();
+
+    median(&deviations)
+}
+
+/// Modified z-score (Iglewicz & Hoaglin). Falls back to the mean absolute deviation when more
+/// than half of the history is the same amount, which makes the MAD zero.
+pub fn robust_z_score(value: f32, history: &[f32]) -> Option<f32> {
+    let center = median(history)?;
+    let mad = median_absolute_deviation(history)?;
+
+    if mad > 0.0 {
+        return Some(0.6745 * (value - center) / mad);
+    }
+
+    let mean_ad = mean(
+        &history
+            .iter()
+            .map(|v| (v - center).abs())
+            .collect::<Vec<f32>>(),
+    )?;
+
+    match mean_ad > 0.0 {
+        true => Some((value - center) / (1.253_314 * mean_ad)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::application::statistics::{median, percentile, robust_z_score, std_dev};
+
+    fn approx(actual: Option<f32>, expected: f32) -> bool {
+        actual.is_some_and(|a| (a - expected).abs() < 0.001)
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(&[4.0, 1.0, 3.0, 2.0]), Some(2.5));
+        assert_eq!(median(&[5.0]), Some(5.0));
+        assert_eq!(median(&[]), None);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values = (1..=10).rev().map(|v| v as f32).collect::<Vec<f32>>();
+
+        // Rank 8.1 sits a tenth of the way from 9 to 10
+        assert!(approx(percentile(&values, 90.0), 9.1));
+        assert_eq!(percentile(&values, 50.0), Some(5.5));
+        assert_eq!(percentile(&values, 0.0), Some(1.0));
+        assert_eq!(percentile(&values, 150.0), Some(10.0));
+        assert_eq!(percentile(&[1.0, 2.0, 3.0], 50.0), Some(2.0));
+        assert_eq!(percentile(&[7.0], 90.0), Some(7.0));
+        assert_eq!(percentile(&[], 90.0), None);
+    }
+
+    #[test]
+    fn test_std_dev() {
+        assert_eq!(
+            std_dev(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]),
+            Some(2.0)
+        );
+        assert!(approx(std_dev(&[1.0, 2.0, 3.0]), (2.0_f32 / 3.0).sqrt()));
+        assert_eq!(std_dev(&[3.0]), Some(0.0));
+        assert_eq!(std_dev(&[]), None);
+    }
+
+    #[test]
+    fn test_robust_z_score() {
+        // Median 14, MAD 2
+        let history = [10.0, 12.0, 14.0, 16.0, 18.0];
+        assert!(approx(robust_z_score(20.0, &history), 0.6745 * 6.0 / 2.0));
+        assert!(approx(robust_z_score(14.0, &history), 0.0));
+
+        // More than half of it is 10, so the MAD is zero and the mean absolute deviation of 6
+        // takes over
+        let history = [10.0, 10.0, 10.0, 20.0, 30.0];
+        assert!(approx(
+            robust_z_score(40.0, &history),
+            30.0 / (1.253_314 * 6.0)
+        ));
+
+        assert_eq!(robust_z_score(40.0, &[10.0, 10.0, 10.0]), None);
+        assert_eq!(robust_z_score(40.0, &[10.0]), None);
+        assert_eq!(robust_z_score(40.0, &[]), None);
+    }
+}
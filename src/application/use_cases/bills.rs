@@ -0,0 +1,668 @@
+use anyhow::{Result, anyhow};
+use chrono::{Days, Local, Months, NaiveDate};
+use std::{collections::HashMap, sync::Arc};
+
+use crate::domain::{
+    entities::{
+        bill_occurrences::{AddBillOccurrenceDto, BillOccurrence, SettleBillOccurrenceDto},
+        monthly_spending::MonthlySpending,
+        my_ledger::RecordMyLedgerDto,
+    },
+    repositories::{bills::BillRepository, spending_scanner::SpendingScannerRepository},
+    value_objects::{
+        bills::{
+            BillOccurrenceModel, BillStatus, MarkBillPaidModel, PostDueBillsModel,
+            PostDueBillsResultModel, PostingMode, UnpaidBillModel, UnpaidBillsModel,
+            UpcomingBillModel, UpcomingBillsFilter, UpcomingBillsModel,
+        },
+        spending_scanner::Grouping,
+    },
+};
+
+const DEFAULT_UPCOMING_DAYS: u32 = 30;
+/// A year ahead, daily items would otherwise expand into one entry per day without bound.
+const MAX_UPCOMING_DAYS: u32 = 366;
+/// How far ahead to look for the next cycle when everything due is settled.
+const NEXT_CYCLE_MONTHS: u32 = 24;
+
+#[derive(Clone)]
+pub struct BillsUseCase {
+    bill_repository: Arc<dyn BillRepository + Send + Sync + 'static>,
+    spending_scanner_repository: Arc<dyn SpendingScannerRepository + Send + Sync + 'static>,
+}
+
+impl BillsUseCase {
+    pub fn new(
+        bill_repository: Arc<dyn BillRepository + Send + Sync + 'static>,
+        spending_scanner_repository: Arc<dyn SpendingScannerRepository + Send + Sync + 'static>,
+    ) -> Self {
+        Self {
+            bill_repository,
+            spending_scanner_repository,
+        }
+    }
+
+    /// Records every occurrence that is due by today and posts it into the ledger, right away
+    /// for automatic bills and once confirmed for the others. Safe to run any number of times,
+    /// an occurrence is only ever posted once.
+    pub async fn post_due_bills(
+        &self,
+        post_due_bills_model: PostDueBillsModel,
+    ) -> Result<PostDueBillsResultModel> {
+        self.post_due_bills_on(post_due_bills_model, Local::now().date_naive())
+            .await
+    }
+
+    async fn post_due_bills_on(
+        &self,
+        post_due_bills_model: PostDueBillsModel,
+        today: NaiveDate,
+    ) -> Result<PostDueBillsResultModel> {
+        let items = self.record_occurrences_until(today).await?;
+
+        let mut posted = Vec::new();
+        let mut awaiting_confirmation = Vec::new();
+        for occurrence in self
+            .bill_repository
+            .view_pending_occurrences(today.to_string())
+            .await?
+        {
+            // Occurrences of removed items are left alone
+            let Some(item) = items.get(&occurrence.monthly_spending_id) else {
+                continue;
+            };
+
+            let confirmed = item.posting_mode() == PostingMode::Auto
+                || post_due_bills_model.confirm.contains(&occurrence.id);
+            if !confirmed {
+                awaiting_confirmation.push(occurrence_model(&occurrence, item));
+                continue;
+            }
+
+            let ledger_id = self
+                .bill_repository
+                .post_occurrence(occurrence.id, ledger_entry(item, &occurrence.due_date))
+                .await?;
+
+            if let Some(ledger_id) = ledger_id {
+                posted.push(BillOccurrenceModel {
+                    status: BillStatus::Paid,
+                    paid_date: Some(occurrence.due_date.to_owned()),
+                    ledger_id: Some(ledger_id),
+                    ..occurrence_model(&occurrence, item)
+                });
+            }
+        }
+
+        Ok(PostDueBillsResultModel {
+            posted,
+            awaiting_confirmation,
+        })
+    }
+
+    /// Every pending cycle due by the end of this month, overdue ones first.
+    pub async fn unpaid_bills(&self) -> Result<UnpaidBillsModel> {
+        self.unpaid_bills_on(Local::now().date_naive()).await
+    }
+
+    async fn unpaid_bills_on(&self, today: NaiveDate) -> Result<UnpaidBillsModel> {
+        let (_, month_end) = Grouping::Month.bucket_bounds(today);
+        let items = self.record_occurrences_until(month_end).await?;
+
+        let bills = self
+            .bill_repository
+            .view_pending_occurrences(month_end.to_string())
+            .await?
+            .iter()
+            .filter_map(|occurrence| {
+                let item = items.get(&occurrence.monthly_spending_id)?;
+                let due_date = NaiveDate::parse_from_str(&occurrence.due_date, "%Y-%m-%d").ok()?;
+                let days_overdue = (today - due_date).num_days().max(0);
+
+                Some(UnpaidBillModel {
+                    bill: occurrence_model(occurrence, item),
+                    overdue: days_overdue > 0,
+                    days_overdue,
+                })
+            })
+            .collect::<Vec<UnpaidBillModel>>();
+
+        Ok(UnpaidBillsModel {
+            unpaid_total: bills.iter().map(|b| b.bill.amount).sum(),
+            overdue_total: bills
+                .iter()
+                .filter(|b| b.overdue)
+                .map(|b| b.bill.amount)
+                .sum(),
+            bills,
+        })
+    }
+
+    /// Settles one cycle of a scheduled payment, by default the oldest unpaid one or, when
+    /// everything due is settled, the next one. Without a `ledger_id` the payment is recorded
+    /// in the ledger on the paid date.
+    pub async fn mark_bill_paid(
+        &self,
+        mark_bill_paid_model: MarkBillPaidModel,
+    ) -> Result<BillOccurrenceModel> {
+        self.mark_bill_paid_on(mark_bill_paid_model, Local::now().date_naive())
+            .await
+    }
+
+    async fn mark_bill_paid_on(
+        &self,
+        mark_bill_paid_model: MarkBillPaidModel,
+        today: NaiveDate,
+    ) -> Result<BillOccurrenceModel> {
+        let items = self.record_occurrences_until(today).await?;
+        let item = items
+            .get(&mark_bill_paid_model.monthly_spending_id)
+            .ok_or_else(|| {
+                anyhow!(
+                    "No monthly spending with id {}",
+                    mark_bill_paid_model.monthly_spending_id
+                )
+            })?;
+
+        let due_date = match &mark_bill_paid_model.due_date {
+            Some(due_date) => {
+                let date = parse_date(due_date)?;
+                if item.recurrence().occurrences(date, date)?.is_empty() {
+                    return Err(anyhow!("{} is not due on {}", item.title, date));
+                }
+                date
+            }
+            None => self.next_unpaid(item, today).await?,
+        };
+        let paid_date = match &mark_bill_paid_model.paid_date {
+            Some(paid_date) => parse_date(paid_date)?,
+            None => today,
+        };
+
+        // Cycles outside the posting window, or in the future, are not recorded yet
+        self.bill_repository
+            .add_occurrences(vec![AddBillOccurrenceDto {
+                monthly_spending_id: item.id,
+                due_date: due_date.to_string(),
+            }])
+            .await?;
+        let occurrence = self.find_occurrence(item.id, due_date).await?;
+
+        let settled = match (mark_bill_paid_model.skip, mark_bill_paid_model.ledger_id) {
+            (true, _) => {
+                self.settle(&occurrence, BillStatus::Skipped, None, None)
+                    .await?
+            }
+            (false, Some(ledger_id)) => {
+                self.settle(
+                    &occurrence,
+                    BillStatus::Paid,
+                    Some(paid_date),
+                    Some(ledger_id),
+                )
+                .await?
+            }
+            (false, None) => self
+                .bill_repository
+                .post_occurrence(occurrence.id, ledger_entry(item, &paid_date.to_string()))
+                .await?
+                .is_some(),
+        };
+        if !settled {
+            return Err(anyhow!(
+                "The {} cycle of {} is already {}",
+                due_date,
+                item.title,
+                occurrence.status().as_str().to_lowercase()
+            ));
+        }
+
+        let occurrence = self.find_occurrence(item.id, due_date).await?;
+
+        Ok(occurrence_model(&occurrence, item))
+    }
+
+    /// Every scheduled payment from today through the next `days` days, in date order. Monthly
+    /// items due on a day a month does not have fall on its last day.
+    pub async fn upcoming_bills(
+        &self,
+        upcoming_bills_filter: UpcomingBillsFilter,
+    ) -> Result<UpcomingBillsModel> {
+        self.upcoming_bills_on(upcoming_bills_filter, Local::now().date_naive())
+            .await
+    }
+
+    async fn upcoming_bills_on(
+        &self,
+        upcoming_bills_filter: UpcomingBillsFilter,
+        today: NaiveDate,
+    ) -> Result<UpcomingBillsModel> {
+        let days = upcoming_bills_filter.days.unwrap_or(DEFAULT_UPCOMING_DAYS);
+        if !(1..=MAX_UPCOMING_DAYS).contains(&days) {
+            return Err(anyhow!(
+                "Days must be between 1 and {}, got {}",
+                MAX_UPCOMING_DAYS,
+                days
+            ));
+        }
+        let end = today
+            .checked_add_days(Days::new(days as u64 - 1))
+            .ok_or_else(|| anyhow!("{} days from {} is past the latest date", days, today))?;
+
+        let items = self
+            .spending_scanner_repository
+            .view_all_monthly_spending()
+            .await?;
+
+        let mut due = Vec::new();
+        for item in &items {
+            for date in item.recurrence().occurrences(today, end)? {
+                due.push((date, item));
+            }
+        }
+        due.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.id.cmp(&b.1.id)));
+
+        let mut running_total = 0.0;
+        let bills = due
+            .into_iter()
+            .map(|(date, item)| {
+                running_total += item.amount;
+
+                UpcomingBillModel {
+                    monthly_spending_id: item.id,
+                    title: item.title.to_owned(),
+                    category: item.category.to_owned(),
+                    amount: item.amount,
+                    due_date: date.to_string(),
+                    running_total,
+                }
+            })
+            .collect::<Vec<UpcomingBillModel>>();
+
+        Ok(UpcomingBillsModel {
+            start: today.to_string(),
+            end: end.to_string(),
+            total: running_total,
+            bills,
+        })
+    }
+
+    /// Remembers every occurrence of each item up to `until`, and returns the items by id. Only
+    /// the days after the latest occurrence already known are expanded, from `post_from` for an
+    /// item that has none yet.
+    async fn record_occurrences_until(
+        &self,
+        until: NaiveDate,
+    ) -> Result<HashMap<i32, MonthlySpending>> {
+        let items = self
+            .spending_scanner_repository
+            .view_all_monthly_spending()
+            .await?;
+        let latest_due_dates = self.bill_repository.latest_due_dates().await?;
+
+        let mut due = Vec::new();
+        for item in &items {
+            let Ok(post_from) = parse_date(&item.post_from) else {
+                continue;
+            };
+            let from = match latest_due_dates.get(&item.id).map(|d| parse_date(d)) {
+                Some(Ok(latest)) => post_from.max(latest + Days::new(1)),
+                _ => post_from,
+            };
+            for date in item.recurrence().occurrences(from, until)? {
+                due.push(AddBillOccurrenceDto {
+                    monthly_spending_id: item.id,
+                    due_date: date.to_string(),
+                });
+            }
+        }
+        self.bill_repository.add_occurrences(due).await?;
+
+        Ok(items.into_iter().map(|item| (item.id, item)).collect())
+    }
+
+    async fn next_unpaid(&self, item: &MonthlySpending, today: NaiveDate) -> Result<NaiveDate> {
+        let oldest_pending = self
+            .bill_repository
+            .view_pending_occurrences(today.to_string())
+            .await?
+            .into_iter()
+            .find(|o| o.monthly_spending_id == item.id);
+        if let Some(occurrence) = oldest_pending {
+            return parse_date(&occurrence.due_date);
+        }
+
+        let tomorrow = today + Days::new(1);
+        item.recurrence()
+            .occurrences(tomorrow, tomorrow + Months::new(NEXT_CYCLE_MONTHS))?
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow!("{} has no unpaid cycle, pass a due_date", item.title))
+    }
+
+    async fn find_occurrence(
+        &self,
+        monthly_spending_id: i32,
+        due_date: NaiveDate,
+    ) -> Result<BillOccurrence> {
+        self.bill_repository
+            .find_occurrence(monthly_spending_id, due_date.to_string())
+            .await?
+            .ok_or_else(|| anyhow!("No bill cycle on {}", due_date))
+    }
+
+    async fn settle(
+        &self,
+        occurrence: &BillOccurrence,
+        status: BillStatus,
+        paid_date: Option<NaiveDate>,
+        ledger_id: Option<i32>,
+    ) -> Result<bool> {
+        self.bill_repository
+            .settle_occurrence(
+                occurrence.id,
+                SettleBillOccurrenceDto {
+                    status: status.as_str().to_string(),
+                    paid_date: paid_date.map(|d| d.to_string()),
+                    ledger_id,
+                },
+            )
+            .await
+    }
+}
+
+/// Scheduled amounts are positive, the ledger stores expenses as negative.
+fn ledger_entry(item: &MonthlySpending, date: &str) -> RecordMyLedgerDto {
+    RecordMyLedgerDto {
+        amount: -item.amount.abs(),
+        category: item.category.to_owned(),
+        description: item.title.to_owned(),
+        date: date.to_string(),
+        income_type: None,
+    }
+}
+
+fn occurrence_model(occurrence: &BillOccurrence, item: &MonthlySpending) -> BillOccurrenceModel {
+    BillOccurrenceModel {
+        id: occurrence.id,
+        monthly_spending_id: item.id,
+        title: item.title.to_owned(),
+        category: item.category.to_owned(),
+        amount: item.amount,
+        due_date: occurrence.due_date.to_owned(),
+        status: occurrence.status(),
+        paid_date: occurrence.paid_date.to_owned(),
+        ledger_id: occurrence.ledger_id,
+    }
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| anyhow!("Invalid date, expected YYYY-MM-DD: {}", date))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use chrono::NaiveDate;
+
+    use crate::{
+        application::use_cases::bills::BillsUseCase,
+        domain::{
+            entities::{bill_occurrences::BillOccurrence, monthly_spending::MonthlySpending},
+            repositories::{
+                bills::MockBillRepository, spending_scanner::MockSpendingScannerRepository,
+            },
+            value_objects::bills::{
+                BillStatus, MarkBillPaidModel, PostDueBillsModel, UpcomingBillsFilter,
+            },
+        },
+    };
+
+    fn monthly_spending(id: i32, title: &str, posting: &str) -> MonthlySpending {
+        MonthlySpending {
+            id,
+            title: title.to_string(),
+            amount: 500.0,
+            frequency: "Monthly".to_string(),
+            interval: 1,
+            anchor_date: "2025-01-31".to_string(),
+            end_date: None,
+            count: None,
+            category: "BILLS".to_string(),
+            posting: posting.to_string(),
+            post_from: "2025-02-01".to_string(),
+        }
+    }
+
+    fn occurrence(id: i32, monthly_spending_id: i32, due_date: &str) -> BillOccurrence {
+        BillOccurrence {
+            id,
+            monthly_spending_id,
+            due_date: due_date.to_string(),
+            ledger_id: None,
+            status: "Pending".to_string(),
+            paid_date: None,
+        }
+    }
+
+    fn bills_use_case(mock_bill_repository: MockBillRepository) -> BillsUseCase {
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_spending_scanner_repository
+            .expect_view_all_monthly_spending()
+            .returning(|| {
+                Box::pin(async {
+                    Ok(vec![
+                        monthly_spending(1, "Internet", "Auto"),
+                        monthly_spending(2, "Gym", "ConfirmFirst"),
+                    ])
+                })
+            });
+
+        BillsUseCase::new(
+            Arc::new(mock_bill_repository),
+            Arc::new(mock_spending_scanner_repository),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_post_due_bills_success() {
+        let mut mock_bill_repository = MockBillRepository::new();
+
+        mock_bill_repository
+            .expect_latest_due_dates()
+            .returning(|| Box::pin(async { Ok(HashMap::from([(1, "2025-02-28".to_string())])) }));
+
+        // Nothing before post_from or already known, Mar 31 for Internet, Feb 28 and Mar 31 for Gym
+        mock_bill_repository
+            .expect_add_occurrences()
+            .withf(|dtos| {
+                dtos.len() == 3
+                    && dtos.iter().all(|d| {
+                        (d.monthly_spending_id == 2 && d.due_date == "2025-02-28")
+                            || d.due_date == "2025-03-31"
+                    })
+            })
+            .returning(|_| Box::pin(async { Ok(()) }));
+
+        mock_bill_repository
+            .expect_view_pending_occurrences()
+            .withf(|until| until == "2025-04-15")
+            .returning(|_| {
+                Box::pin(async {
+                    Ok(vec![
+                        occurrence(10, 1, "2025-02-28"),
+                        occurrence(11, 2, "2025-02-28"),
+                        occurrence(12, 1, "2025-03-31"),
+                        occurrence(13, 2, "2025-03-31"),
+                    ])
+                })
+            });
+
+        mock_bill_repository
+            .expect_post_occurrence()
+            .withf(|id, dto| *id != 13 && dto.amount == -500.0 && dto.category == "BILLS")
+            .times(3)
+            .returning(|id, _| Box::pin(async move { Ok(Some(id + 100)) }));
+
+        let bills_use_case = bills_use_case(mock_bill_repository);
+
+        let result = bills_use_case
+            .post_due_bills_on(
+                PostDueBillsModel { confirm: vec![11] },
+                NaiveDate::from_ymd_opt(2025, 4, 15).unwrap(),
+            )
+            .await;
+
+        assert!(result.is_ok());
+
+        let result = result.unwrap();
+
+        assert_eq!(result.posted.len(), 3);
+        assert_eq!(result.posted[1].title, "Gym");
+        assert_eq!(result.posted[1].status, BillStatus::Paid);
+        assert_eq!(result.posted[1].ledger_id, Some(111));
+        assert_eq!(result.awaiting_confirmation.len(), 1);
+        assert_eq!(result.awaiting_confirmation[0].id, 13);
+    }
+
+    #[tokio::test]
+    async fn test_unpaid_bills_success() {
+        let mut mock_bill_repository = MockBillRepository::new();
+
+        mock_bill_repository
+            .expect_latest_due_dates()
+            .returning(|| Box::pin(async { Ok(HashMap::new()) }));
+        mock_bill_repository
+            .expect_add_occurrences()
+            .returning(|_| Box::pin(async { Ok(()) }));
+
+        // Recorded up to the end of the month, so bills due later this month count as unpaid
+        mock_bill_repository
+            .expect_view_pending_occurrences()
+            .withf(|until| until == "2025-04-30")
+            .returning(|_| {
+                Box::pin(async {
+                    Ok(vec![
+                        occurrence(13, 2, "2025-03-31"),
+                        occurrence(14, 1, "2025-04-30"),
+                    ])
+                })
+            });
+
+        let bills_use_case = bills_use_case(mock_bill_repository);
+
+        let result = bills_use_case
+            .unpaid_bills_on(NaiveDate::from_ymd_opt(2025, 4, 15).unwrap())
+            .await;
+
+        assert!(result.is_ok());
+
+        let result = result.unwrap();
+
+        assert_eq!(result.unpaid_total, 1000.0);
+        assert_eq!(result.overdue_total, 500.0);
+        assert!(result.bills[0].overdue);
+        assert_eq!(result.bills[0].days_overdue, 15);
+        assert!(!result.bills[1].overdue);
+        assert_eq!(result.bills[1].days_overdue, 0);
+    }
+
+    #[tokio::test]
+    async fn test_mark_bill_paid_success() {
+        let mut mock_bill_repository = MockBillRepository::new();
+
+        mock_bill_repository
+            .expect_latest_due_dates()
+            .returning(|| Box::pin(async { Ok(HashMap::new()) }));
+        mock_bill_repository
+            .expect_add_occurrences()
+            .returning(|_| Box::pin(async { Ok(()) }));
+
+        mock_bill_repository
+            .expect_view_pending_occurrences()
+            .returning(|_| Box::pin(async { Ok(vec![occurrence(13, 2, "2025-03-31")]) }));
+
+        let mut lookups = 0;
+        mock_bill_repository
+            .expect_find_occurrence()
+            .withf(|id, due_date| *id == 2 && due_date == "2025-03-31")
+            .returning(move |_, _| {
+                lookups += 1;
+                let mut found = occurrence(13, 2, "2025-03-31");
+                if lookups > 1 {
+                    found.status = "Paid".to_string();
+                    found.paid_date = Some("2025-04-02".to_string());
+                    found.ledger_id = Some(7);
+                }
+                Box::pin(async move { Ok(Some(found)) })
+            });
+
+        mock_bill_repository
+            .expect_settle_occurrence()
+            .withf(|id, dto| *id == 13 && dto.status == "Paid" && dto.ledger_id == Some(7))
+            .returning(|_, _| Box::pin(async { Ok(true) }));
+
+        let bills_use_case = bills_use_case(mock_bill_repository);
+
+        let mark_paid = |due_date: Option<&str>| {
+            bills_use_case.mark_bill_paid_on(
+                MarkBillPaidModel {
+                    monthly_spending_id: 2,
+                    due_date: due_date.map(str::to_string),
+                    paid_date: Some("2025-04-02".to_string()),
+                    ledger_id: Some(7),
+                    skip: false,
+                },
+                NaiveDate::from_ymd_opt(2025, 4, 15).unwrap(),
+            )
+        };
+
+        let result = mark_paid(None).await;
+
+        assert!(result.is_ok());
+
+        let result = result.unwrap();
+
+        assert_eq!(result.status, BillStatus::Paid);
+        assert_eq!(result.paid_date.as_deref(), Some("2025-04-02"));
+        assert_eq!(result.ledger_id, Some(7));
+
+        assert!(mark_paid(Some("2025-03-30")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_upcoming_bills_success() {
+        let bills_use_case = bills_use_case(MockBillRepository::new());
+
+        let result = bills_use_case
+            .upcoming_bills_on(
+                UpcomingBillsFilter { days: Some(40) },
+                NaiveDate::from_ymd_opt(2025, 2, 20).unwrap(),
+            )
+            .await;
+
+        assert!(result.is_ok());
+
+        let result = result.unwrap();
+
+        // Anchored on Jan 31, both items are due on the last day of February
+        assert_eq!(result.end, "2025-03-31");
+        assert_eq!(result.bills.len(), 4);
+        assert_eq!(result.bills[0].due_date, "2025-02-28");
+        assert_eq!(result.bills[1].due_date, "2025-02-28");
+        assert_eq!(result.bills[2].due_date, "2025-03-31");
+        assert_eq!(result.bills[3].running_total, 2000.0);
+        assert_eq!(result.total, 2000.0);
+
+        for days in [0, 367, u32::MAX] {
+            let invalid = bills_use_case
+                .upcoming_bills(UpcomingBillsFilter { days: Some(days) })
+                .await;
+
+            assert!(invalid.is_err());
+        }
+    }
+}
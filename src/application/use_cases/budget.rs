@@ -0,0 +1,644 @@
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Local, Months, NaiveDate};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Arc,
+};
+
+use crate::{
+    application::use_cases::spending_scanner::day_after,
+    domain::{
+        entities::{budgets::Budget, my_ledger::RecordMyLedgerDto},
+        repositories::{budget::BudgetRepository, spending_scanner::SpendingScannerRepository},
+        value_objects::{
+            budget::{
+                BudgetModel, BudgetStatusFilter, BudgetStatusModel, BudgetWarningModel,
+                MonthlyBudgetStatusModel, RolloverPolicy, SetBudgetModel,
+            },
+            spending_scanner::Grouping,
+        },
+    },
+};
+
+/// Percentages of the available budget that raise a warning when an expense is recorded.
+const WARNING_THRESHOLDS: [f32; 2] = [80.0, 100.0];
+
+#[derive(Clone)]
+pub struct BudgetUseCase {
+    budget_repository: Arc<dyn BudgetRepository + Send + Sync + 'static>,
+    spending_scanner_repository: Arc<dyn SpendingScannerRepository + Send + Sync + 'static>,
+}
+
+impl BudgetUseCase {
+    pub fn new(
+        budget_repository: Arc<dyn BudgetRepository + Send + Sync + 'static>,
+        spending_scanner_repository: Arc<dyn SpendingScannerRepository + Send + Sync + 'static>,
+    ) -> Self {
+        Self {
+            budget_repository,
+            spending_scanner_repository,
+        }
+    }
+
+    pub async fn set_budget(&self, set_budget_model: SetBudgetModel) -> Result<i32> {
+        if !set_budget_model.monthly_limit.is_finite() || set_budget_model.monthly_limit <= 0.0 {
+            return Err(anyhow!(
+                "Monthly limit must be greater than zero, got {}",
+                set_budget_model.monthly_limit
+            ));
+        }
+
+        let effective_from = set_budget_model
+            .effective_from
+            .as_deref()
+            .map(parse_month)
+            .transpose()?;
+        let effective_to = set_budget_model
+            .effective_to
+            .as_deref()
+            .map(parse_month)
+            .transpose()?;
+
+        if let Some(cap) = set_budget_model.rollover_cap
+            && (!cap.is_finite() || cap < 0.0)
+        {
+            return Err(anyhow!("Rollover cap must not be negative, got {}", cap));
+        }
+
+        if let (Some(from), Some(to)) = (effective_from, effective_to)
+            && from > to
+        {
+            return Err(anyhow!(
+                "effective_from {} is after effective_to {}",
+                month_label(from),
+                month_label(to)
+            ));
+        }
+
+        let mut set_budget_dto = set_budget_model.to_dto();
+        set_budget_dto.effective_from = effective_from.map(month_label);
+        set_budget_dto.effective_to = effective_to.map(month_label);
+
+        self.budget_repository.set_budget(set_budget_dto).await
+    }
+
+    pub async fn list_budgets(&self) -> Result<Vec<BudgetModel>> {
+        let results = self.budget_repository.view_all_budgets().await?;
+
+        Ok(results
+            .iter()
+            .map(|r| r.to_model())
+            .collect::<Vec<BudgetModel>>())
+    }
+
+    /// Compares each category's budget with what was spent in the month, most used first.
+    /// Spending is the sum of expenses only, a refund does not free up budget.
+    pub async fn status(
+        &self,
+        budget_status_filter: BudgetStatusFilter,
+    ) -> Result<MonthlyBudgetStatusModel> {
+        let today = Local::now().date_naive();
+        let (month_start, month_end) = match budget_status_filter.month.as_deref() {
+            Some(month) => Grouping::Month.bucket_bounds(parse_month(month)?),
+            None => Grouping::Month.bucket_bounds(today),
+        };
+        let month = month_label(month_start);
+
+        let days_in_month = month_end.day();
+        let days_elapsed = if today > month_end {
+            days_in_month
+        } else if today < month_start {
+            0
+        } else {
+            today.day()
+        };
+
+        let budgets = self.budget_repository.view_all_budgets().await?;
+
+        let spent_by_category = self
+            .spending_scanner_repository
+            .cash_flow_by_category(month_start.to_string(), day_after(month_end))
+            .await?
+            .into_iter()
+            .map(|c| (c.category, -c.expense.unwrap_or(0.0)))
+            .collect::<HashMap<String, f32>>();
+
+        // Without any rollover there is nothing to carry, so the history is not needed
+        let history = match budgets
+            .iter()
+            .any(|b| b.rollover_policy() != RolloverPolicy::None)
+        {
+            true => {
+                self.budget_repository
+                    .monthly_spent(month_start.to_string())
+                    .await?
+            }
+            false => Vec::new(),
+        };
+        let history_by_category = history.into_iter().fold(
+            HashMap::new(),
+            |mut acc: HashMap<String, BTreeMap<String, f32>>, m| {
+                acc.entry(m.category).or_default().insert(m.month, m.spent);
+                acc
+            },
+        );
+
+        let mut statuses = budgets_for_month(&budgets, &month)
+            .into_iter()
+            .map(|budget| {
+                let spent = spent_by_category
+                    .get(&budget.category)
+                    .copied()
+                    .unwrap_or(0.0);
+                let carried = carried_into(
+                    &budgets,
+                    &budget.category,
+                    month_start,
+                    history_by_category.get(&budget.category),
+                );
+                let available = budget.monthly_limit + carried;
+
+                BudgetStatusModel {
+                    category: budget.category.to_owned(),
+                    monthly_limit: budget.monthly_limit,
+                    carried,
+                    available,
+                    spent,
+                    remaining: available - spent,
+                    percent_used: match available > 0.0 {
+                        true => spent / available * 100.0,
+                        false => 100.0,
+                    },
+                    projected: match days_elapsed {
+                        0 => spent,
+                        _ => spent / days_elapsed as f32 * days_in_month as f32,
+                    },
+                }
+            })
+            .collect::<Vec<BudgetStatusModel>>();
+        statuses.sort_by(|a, b| {
+            b.percent_used
+                .total_cmp(&a.percent_used)
+                .then(a.category.cmp(&b.category))
+        });
+
+        Ok(MonthlyBudgetStatusModel {
+            month,
+            days_elapsed,
+            days_in_month,
+            budgets: statuses,
+        })
+    }
+
+    /// Checks a freshly recorded expense against its category's budget for the month it was
+    /// recorded in.
+    pub async fn check_recorded(
+        &self,
+        record_my_ledger_dto: RecordMyLedgerDto,
+    ) -> Result<Option<BudgetWarningModel>> {
+        if record_my_ledger_dto.amount >= 0.0 {
+            return Ok(None);
+        }
+
+        let Some(month) = record_my_ledger_dto.date.get(..7) else {
+            return Ok(None);
+        };
+
+        let status = self
+            .status(BudgetStatusFilter {
+                month: Some(month.to_string()),
+            })
+            .await?;
+
+        let Some(budget) = status
+            .budgets
+            .into_iter()
+            .find(|b| b.category == record_my_ledger_dto.category)
+        else {
+            return Ok(None);
+        };
+
+        let threshold = match budget.available > 0.0 {
+            true => {
+                // The entry is already in the ledger, so the status includes it
+                let percent_before =
+                    (budget.spent + record_my_ledger_dto.amount) / budget.available * 100.0;
+
+                // Only the expense that crosses a threshold warns, later ones above it stay quiet
+                WARNING_THRESHOLDS
+                    .iter()
+                    .rev()
+                    .find(|threshold| {
+                        percent_before < **threshold && budget.percent_used >= **threshold
+                    })
+                    .copied()
+            }
+            // Nothing is left to spend, so every expense goes over the budget
+            false => (budget.spent > budget.available).then_some(100.0),
+        };
+
+        Ok(threshold.map(|threshold| BudgetWarningModel {
+            category: budget.category,
+            month: status.month,
+            threshold,
+            percent_used: budget.percent_used,
+            available: budget.available,
+            spent: budget.spent,
+            remaining: budget.remaining,
+        }))
+    }
+}
+
+/// The budget that applies to each category in `month`.
+fn budgets_for_month<'a>(budgets: &'a [Budget], month: &str) -> Vec<&'a Budget> {
+    budgets
+        .iter()
+        .map(|b| b.category.as_str())
+        .collect::<BTreeSet<&str>>()
+        .into_iter()
+        .filter_map(|category| budget_for(budgets, category, month))
+        .collect()
+}
+
+/// When periods overlap, the one that started most recently wins, then the most recently added.
+fn budget_for<'a>(budgets: &'a [Budget], category: &str, month: &str) -> Option<&'a Budget> {
+    budgets
+        .iter()
+        .filter(|b| b.category == category && b.covers(month))
+        .max_by(|a, b| (&a.effective_from, a.id).cmp(&(&b.effective_from, b.id)))
+}
+
+/// Replays every month before `month_start`, from the first one with a budget or spending in
+/// the category, so a past month always shows the same carried amount. A month without a
+/// budget resets the carry.
+fn carried_into(
+    budgets: &[Budget],
+    category: &str,
+    month_start: NaiveDate,
+    spent_by_month: Option<&BTreeMap<String, f32>>,
+) -> f32 {
+    let first_budgeted = budgets
+        .iter()
+        .filter(|b| b.category == category)
+        .filter_map(|b| b.effective_from.as_deref())
+        .min();
+    let first_spent = spent_by_month
+        .and_then(|m| m.keys().next())
+        .map(String::as_str);
+
+    let Some(Ok(mut month)) = [first_budgeted, first_spent]
+        .into_iter()
+        .flatten()
+        .min()
+        .map(parse_month)
+    else {
+        return 0.0;
+    };
+
+    let mut carried = 0.0;
+    while month < month_start {
+        let label = month_label(month);
+
+        carried = match budget_for(budgets, category, &label) {
+            Some(budget) => {
+                let spent = spent_by_month
+                    .and_then(|m| m.get(&label))
+                    .copied()
+                    .unwrap_or(0.0);
+
+                budget.carry(budget.monthly_limit + carried - spent)
+            }
+            None => 0.0,
+        };
+        month = month + Months::new(1);
+    }
+
+    carried
+}
+
+fn parse_month(month: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+        .map_err(|_| anyhow!("Invalid month, expected YYYY-MM: {}", month))
+}
+
+fn month_label(date: NaiveDate) -> String {
+    date.format("%Y-%m").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        application::use_cases::budget::BudgetUseCase,
+        domain::{
+            entities::{
+                budgets::{Budget, MonthlySpent},
+                my_ledger::{CategoryCashFlow, RecordMyLedgerDto},
+            },
+            repositories::{
+                budget::MockBudgetRepository, spending_scanner::MockSpendingScannerRepository,
+            },
+            value_objects::budget::{BudgetStatusFilter, RolloverPolicy, SetBudgetModel},
+        },
+    };
+
+    fn budget(id: i32, category: &str, monthly_limit: f32, from: Option<&str>) -> Budget {
+        Budget {
+            id,
+            category: category.to_string(),
+            monthly_limit,
+            effective_from: from.map(str::to_string),
+            effective_to: None,
+            rollover: "None".to_string(),
+            rollover_cap: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_budget_success() {
+        let mut mock_budget_repository = MockBudgetRepository::new();
+
+        mock_budget_repository
+            .expect_set_budget()
+            .withf(|dto| {
+                dto.category == "FOOD"
+                    && dto.effective_from.as_deref() == Some("2025-04")
+                    && dto.effective_to.is_none()
+            })
+            .returning(|_| Box::pin(async { Ok(1) }));
+
+        let budget_use_case = BudgetUseCase::new(
+            Arc::new(mock_budget_repository),
+            Arc::new(MockSpendingScannerRepository::new()),
+        );
+
+        let result = budget_use_case
+            .set_budget(SetBudgetModel {
+                category: "Food".to_string(),
+                monthly_limit: 5000.0,
+                effective_from: Some("2025-4".to_string()),
+                effective_to: None,
+                rollover: RolloverPolicy::None,
+                rollover_cap: None,
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), 1);
+
+        let invalid = budget_use_case
+            .set_budget(SetBudgetModel {
+                category: "Food".to_string(),
+                monthly_limit: 5000.0,
+                effective_from: Some("2025-06".to_string()),
+                effective_to: Some("2025-05".to_string()),
+                rollover: RolloverPolicy::None,
+                rollover_cap: None,
+            })
+            .await;
+
+        assert!(invalid.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_status_success() {
+        let mut mock_budget_repository = MockBudgetRepository::new();
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_budget_repository
+            .expect_view_all_budgets()
+            .returning(|| {
+                Box::pin(async {
+                    Ok(vec![
+                        budget(1, "FOOD", 4000.0, None),
+                        budget(2, "FOOD", 6000.0, Some("2025-03")),
+                        budget(3, "TRANSPORT", 1000.0, None),
+                        budget(4, "SHOPPING", 2000.0, Some("2025-05")),
+                    ])
+                })
+            });
+
+        mock_spending_scanner_repository
+            .expect_cash_flow_by_category()
+            .withf(|start, end| start == "2025-04-01" && end == "2025-05-01")
+            .returning(|_, _| {
+                Box::pin(async {
+                    Ok(vec![
+                        CategoryCashFlow {
+                            category: "FOOD".to_string(),
+                            expense: Some(-4500.0),
+                            income: Some(200.0),
+                        },
+                        CategoryCashFlow {
+                            category: "TRANSPORT".to_string(),
+                            expense: Some(-1250.0),
+                            income: Some(0.0),
+                        },
+                    ])
+                })
+            });
+
+        let budget_use_case = BudgetUseCase::new(
+            Arc::new(mock_budget_repository),
+            Arc::new(mock_spending_scanner_repository),
+        );
+
+        let result = budget_use_case
+            .status(BudgetStatusFilter {
+                month: Some("2025-04".to_string()),
+            })
+            .await;
+
+        assert!(result.is_ok());
+
+        let status = result.unwrap();
+
+        assert_eq!(status.month, "2025-04");
+        assert_eq!(status.days_elapsed, 30);
+        assert_eq!(status.days_in_month, 30);
+        assert_eq!(status.budgets.len(), 2);
+
+        assert_eq!(status.budgets[0].category, "TRANSPORT");
+        assert_eq!(status.budgets[0].remaining, -250.0);
+        assert_eq!(status.budgets[0].percent_used, 125.0);
+
+        assert_eq!(status.budgets[1].category, "FOOD");
+        assert_eq!(status.budgets[1].monthly_limit, 6000.0);
+        assert_eq!(status.budgets[1].spent, 4500.0);
+        assert_eq!(status.budgets[1].projected, 4500.0);
+    }
+
+    #[tokio::test]
+    async fn test_status_carries_rollover() {
+        let mut mock_budget_repository = MockBudgetRepository::new();
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_budget_repository
+            .expect_view_all_budgets()
+            .returning(|| {
+                let rollover = |mut budget: Budget, policy: &str, cap: Option<f32>| {
+                    budget.rollover = policy.to_string();
+                    budget.rollover_cap = cap;
+                    budget
+                };
+
+                Box::pin(async move {
+                    Ok(vec![
+                        rollover(
+                            budget(1, "FOOD", 1000.0, Some("2025-01")),
+                            "CarryBoth",
+                            None,
+                        ),
+                        rollover(
+                            budget(2, "COFFEE", 500.0, Some("2025-02")),
+                            "CarrySurplus",
+                            Some(300.0),
+                        ),
+                    ])
+                })
+            });
+
+        mock_budget_repository
+            .expect_monthly_spent()
+            .withf(|before| before == "2025-04-01")
+            .returning(|_| {
+                let spent = |category: &str, month: &str, spent: f32| MonthlySpent {
+                    category: category.to_string(),
+                    month: month.to_string(),
+                    spent,
+                };
+
+                Box::pin(async move {
+                    Ok(vec![
+                        // Spending before the budget existed does not carry
+                        spent("COFFEE", "2025-01", 900.0),
+                        spent("COFFEE", "2025-02", 100.0),
+                        spent("COFFEE", "2025-03", 600.0),
+                        spent("FOOD", "2025-01", 800.0),
+                        spent("FOOD", "2025-02", 1500.0),
+                    ])
+                })
+            });
+
+        mock_spending_scanner_repository
+            .expect_cash_flow_by_category()
+            .returning(|_, _| Box::pin(async { Ok(Vec::new()) }));
+
+        let budget_use_case = BudgetUseCase::new(
+            Arc::new(mock_budget_repository),
+            Arc::new(mock_spending_scanner_repository),
+        );
+
+        let status = budget_use_case
+            .status(BudgetStatusFilter {
+                month: Some("2025-04".to_string()),
+            })
+            .await
+            .unwrap();
+
+        let carried = |category: &str| {
+            status
+                .budgets
+                .iter()
+                .find(|b| b.category == category)
+                .map(|b| (b.carried, b.available))
+                .unwrap()
+        };
+
+        // FOOD: +200, then -300, then +700 in an untouched March
+        assert_eq!(carried("FOOD"), (700.0, 1700.0));
+        // COFFEE: +400 capped at 300, then 800 - 600 leaves 200
+        assert_eq!(carried("COFFEE"), (200.0, 700.0));
+    }
+
+    #[tokio::test]
+    async fn test_check_recorded_success() {
+        let mut mock_budget_repository = MockBudgetRepository::new();
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_budget_repository
+            .expect_view_all_budgets()
+            .returning(|| {
+                Box::pin(async {
+                    Ok(vec![
+                        budget(1, "FOOD", 1000.0, None),
+                        budget(2, "COFFEE", 1000.0, None),
+                        budget(3, "RENT", 1000.0, None),
+                        budget(4, "DINING", 1000.0, None),
+                        budget(5, "GIFTS", 0.0, None),
+                    ])
+                })
+            });
+
+        mock_spending_scanner_repository
+            .expect_cash_flow_by_category()
+            .withf(|start, _| start == "2025-04-01")
+            .returning(|_, _| {
+                Box::pin(async {
+                    Ok(vec![
+                        CategoryCashFlow {
+                            category: "FOOD".to_string(),
+                            expense: Some(-820.0),
+                            income: Some(0.0),
+                        },
+                        CategoryCashFlow {
+                            category: "COFFEE".to_string(),
+                            expense: Some(-1030.0),
+                            income: Some(0.0),
+                        },
+                        CategoryCashFlow {
+                            category: "DINING".to_string(),
+                            expense: Some(-900.0),
+                            income: Some(0.0),
+                        },
+                        CategoryCashFlow {
+                            category: "RENT".to_string(),
+                            expense: Some(-500.0),
+                            income: Some(0.0),
+                        },
+                        CategoryCashFlow {
+                            category: "GIFTS".to_string(),
+                            expense: Some(-50.0),
+                            income: Some(0.0),
+                        },
+                    ])
+                })
+            });
+
+        let budget_use_case = BudgetUseCase::new(
+            Arc::new(mock_budget_repository),
+            Arc::new(mock_spending_scanner_repository),
+        );
+
+        let check = |category: &str| {
+            budget_use_case.check_recorded(RecordMyLedgerDto {
+                amount: -50.0,
+                category: category.to_string(),
+                description: "Test".to_string(),
+                date: "2025-04-15".to_string(),
+                income_type: None,
+            })
+        };
+
+        // 770 -> 820
+        let food = check("FOOD").await.unwrap().unwrap();
+        assert_eq!(food.threshold, 80.0);
+        assert_eq!(food.remaining, 180.0);
+
+        // 980 -> 1030
+        let coffee = check("COFFEE").await.unwrap().unwrap();
+        assert_eq!(coffee.threshold, 100.0);
+        assert_eq!(coffee.remaining, -30.0);
+
+        // 850 -> 900, 80% was already reached by an earlier expense
+        assert!(check("DINING").await.unwrap().is_none());
+
+        // Nothing budgeted, so the expense goes straight over
+        let gifts = check("GIFTS").await.unwrap().unwrap();
+        assert_eq!(gifts.threshold, 100.0);
+        assert_eq!(gifts.remaining, -50.0);
+
+        assert!(check("RENT").await.unwrap().is_none());
+        assert!(check("TRAVEL").await.unwrap().is_none());
+    }
+}
@@ -60,6 +60,7 @@ mod tests {
             amount: 100.0,
             category: "Food".to_string(),
             description: "Lunch".to_string(),
+            income_type: None,
         };
 
         let result = cash_flow_use_case.record(record_cash_flow_model).await;
@@ -82,6 +83,7 @@ mod tests {
             category: "Food".to_string(),
             description: "Lunch".to_string(),
             date: "2023-10-01".to_string(),
+            income_type: None,
         };
 
         let result = cash_flow_use_case
@@ -0,0 +1,420 @@
+use anyhow::{Result, anyhow};
+use chrono::Local;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Arc,
+};
+
+use crate::{
+    application::use_cases::spending_scanner::{LIFETIME_END, LIFETIME_START},
+    domain::{
+        entities::envelope_transfers::AddEnvelopeTransferDto,
+        repositories::{envelope::EnvelopeRepository, spending_scanner::SpendingScannerRepository},
+        value_objects::envelope::{
+            AssignEnvelopeModel, EnvelopeBalanceModel, EnvelopeStatusModel, MoveEnvelopeModel,
+        },
+    },
+};
+
+#[derive(Clone)]
+pub struct EnvelopeUseCase {
+    envelope_repository: Arc<dyn EnvelopeRepository + Send + Sync + 'static>,
+    spending_scanner_repository: Arc<dyn SpendingScannerRepository + Send + Sync + 'static>,
+}
+
+impl EnvelopeUseCase {
+    pub fn new(
+        envelope_repository: Arc<dyn EnvelopeRepository + Send + Sync + 'static>,
+        spending_scanner_repository: Arc<dyn SpendingScannerRepository + Send + Sync + 'static>,
+    ) -> Self {
+        Self {
+            envelope_repository,
+            spending_scanner_repository,
+        }
+    }
+
+    /// Moves money from the unassigned pool into an envelope. Only income that has not been
+    /// assigned yet can be, so every envelope is backed by money that was actually received.
+    pub async fn assign(&self, assign_envelope_model: AssignEnvelopeModel) -> Result<i32> {
+        let amount = positive_amount(assign_envelope_model.amount)?;
+        let envelope = envelope_name(&assign_envelope_model.envelope)?;
+
+        let transfer_id = self
+            .envelope_repository
+            .add_transfer(AddEnvelopeTransferDto {
+                from_envelope: None,
+                to_envelope: Some(envelope.to_owned()),
+                amount,
+                date: Local::now().date_naive().to_string(),
+            })
+            .await?;
+
+        match transfer_id {
+            Some(id) => Ok(id),
+            None => {
+                let status = self.status().await?;
+                Err(anyhow!(
+                    "Cannot assign {:.2} to {}, only {:.2} is unassigned",
+                    amount,
+                    envelope,
+                    status.unassigned
+                ))
+            }
+        }
+    }
+
+    /// Returns money an envelope has not spent to the unassigned pool.
+    pub async fn unassign(&self, unassign_envelope_model: AssignEnvelopeModel) -> Result<i32> {
+        let amount = positive_amount(unassign_envelope_model.amount)?;
+        let envelope = envelope_name(&unassign_envelope_model.envelope)?;
+
+        let transfer_id = self
+            .envelope_repository
+            .add_transfer(AddEnvelopeTransferDto {
+                from_envelope: Some(envelope.to_owned()),
+                to_envelope: None,
+                amount,
+                date: Local::now().date_naive().to_string(),
+            })
+            .await?;
+
+        match transfer_id {
+            Some(id) => Ok(id),
+            None => Err(anyhow!(
+                "Cannot unassign {:.2} from {}, only {:.2} is available",
+                amount,
+                envelope,
+                self.available(&envelope).await?
+            )),
+        }
+    }
+
+    pub async fn move_between(&self, move_envelope_model: MoveEnvelopeModel) -> Result<i32> {
+        let amount = positive_amount(move_envelope_model.amount)?;
+        let from_envelope = envelope_name(&move_envelope_model.from_envelope)?;
+        let to_envelope = envelope_name(&move_envelope_model.to_envelope)?;
+
+        if from_envelope == to_envelope {
+            return Err(anyhow!(
+                "Cannot move money from {} to itself",
+                from_envelope
+            ));
+        }
+
+        let transfer_id = self
+            .envelope_repository
+            .add_transfer(AddEnvelopeTransferDto {
+                from_envelope: Some(from_envelope.to_owned()),
+                to_envelope: Some(to_envelope),
+                amount,
+                date: Local::now().date_naive().to_string(),
+            })
+            .await?;
+
+        match transfer_id {
+            Some(id) => Ok(id),
+            None => Err(anyhow!(
+                "Cannot move {:.2} out of {}, only {:.2} is available",
+                amount,
+                from_envelope,
+                self.available(&from_envelope).await?
+            )),
+        }
+    }
+
+    async fn available(&self, envelope: &str) -> Result<f32> {
+        Ok(self
+            .status()
+            .await?
+            .envelopes
+            .iter()
+            .find(|e| e.envelope == envelope)
+            .map(|e| e.available)
+            .unwrap_or(0.0))
+    }
+
+    /// Income from the whole ledger feeds the unassigned pool and each envelope pays for the
+    /// expenses in its category from the day money was first put into it. Earlier spending, and
+    /// spending in a category without an envelope, comes out of the pool, so assigning never
+    /// grows the pool and `unassigned` plus every `available` always adds up to the ledger
+    /// balance.
+    pub async fn status(&self) -> Result<EnvelopeStatusModel> {
+        let transfers = self.envelope_repository.view_all_transfers().await?;
+        let cash_flows = self
+            .spending_scanner_repository
+            .cash_flow_by_category(LIFETIME_START.to_string(), LIFETIME_END.to_string())
+            .await?;
+
+        let mut assigned = BTreeMap::new();
+        let mut opened_on: BTreeMap<String, String> = BTreeMap::new();
+        for t in &transfers {
+            if let Some(from) = &t.from_envelope {
+                *assigned.entry(from.to_owned()).or_insert(0.0) -= t.amount;
+            }
+            if let Some(to) = &t.to_envelope {
+                *assigned.entry(to.to_owned()).or_insert(0.0) += t.amount;
+            }
+            for envelope in [&t.from_envelope, &t.to_envelope].into_iter().flatten() {
+                let opened = opened_on
+                    .entry(envelope.to_owned())
+                    .or_insert_with(|| t.date.to_owned());
+                if t.date < *opened {
+                    *opened = t.date.to_owned();
+                }
+            }
+        }
+
+        // One query per opening day, envelopes are usually set up together
+        let mut spent_since = HashMap::new();
+        for date in opened_on.values().collect::<BTreeSet<&String>>() {
+            let since = self
+                .spending_scanner_repository
+                .cash_flow_by_category(date.to_owned(), LIFETIME_END.to_string())
+                .await?;
+            spent_since.insert(date.to_owned(), since);
+        }
+
+        let income = cash_flows
+            .iter()
+            .map(|c| c.income.unwrap_or(0.0))
+            .sum::<f32>();
+        let total_spent = cash_flows
+            .iter()
+            .map(|c| -c.expense.unwrap_or(0.0))
+            .sum::<f32>();
+
+        let envelopes = assigned
+            .iter()
+            .map(|(envelope, assigned)| {
+                let spent = opened_on
+                    .get(envelope)
+                    .and_then(|date| spent_since.get(date))
+                    .and_then(|since| since.iter().find(|c| &c.category == envelope))
+                    .map(|c| -c.expense.unwrap_or(0.0))
+                    .unwrap_or(0.0);
+
+                EnvelopeBalanceModel {
+                    envelope: envelope.to_owned(),
+                    assigned: *assigned,
+                    spent,
+                    available: assigned - spent,
+                }
+            })
+            .collect::<Vec<EnvelopeBalanceModel>>();
+
+        let unenveloped_spent = total_spent - envelopes.iter().map(|e| e.spent).sum::<f32>();
+
+        Ok(EnvelopeStatusModel {
+            income,
+            unassigned: income - assigned.values().sum::<f32>() - unenveloped_spent,
+            unenveloped_spent,
+            envelopes,
+        })
+    }
+}
+
+fn positive_amount(amount: f32) -> Result<f32> {
+    match amount.is_finite() && amount > 0.0 {
+        true => Ok(amount),
+        false => Err(anyhow!("Amount must be greater than zero, got {}", amount)),
+    }
+}
+
+/// Envelopes match ledger categories, which are stored in upper case.
+fn envelope_name(envelope: &str) -> Result<String> {
+    match envelope.trim() {
+        "" => Err(anyhow!("Envelope name must not be empty")),
+        name => Ok(name.to_uppercase()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        application::use_cases::{envelope::EnvelopeUseCase, spending_scanner::LIFETIME_START},
+        domain::{
+            entities::{envelope_transfers::EnvelopeTransfer, my_ledger::CategoryCashFlow},
+            repositories::{
+                envelope::MockEnvelopeRepository, spending_scanner::MockSpendingScannerRepository,
+            },
+            value_objects::envelope::{AssignEnvelopeModel, MoveEnvelopeModel},
+        },
+    };
+
+    fn transfer(id: i32, from: Option<&str>, to: Option<&str>, amount: f32) -> EnvelopeTransfer {
+        EnvelopeTransfer {
+            id,
+            from_envelope: from.map(str::to_string),
+            to_envelope: to.map(str::to_string),
+            amount,
+            date: "2025-04-01".to_string(),
+        }
+    }
+
+    fn cash_flow(category: &str, expense: f32, income: f32) -> CategoryCashFlow {
+        CategoryCashFlow {
+            category: category.to_string(),
+            expense: Some(expense),
+            income: Some(income),
+        }
+    }
+
+    fn envelope_use_case(mut mock_envelope_repository: MockEnvelopeRepository) -> EnvelopeUseCase {
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_envelope_repository
+            .expect_view_all_transfers()
+            .returning(|| {
+                Box::pin(async {
+                    Ok(vec![
+                        transfer(1, None, Some("FOOD"), 8000.0),
+                        transfer(2, None, Some("RENT"), 12000.0),
+                        transfer(3, Some("RENT"), Some("FOOD"), 1000.0),
+                    ])
+                })
+            });
+
+        mock_spending_scanner_repository
+            .expect_cash_flow_by_category()
+            .returning(|_, _| {
+                Box::pin(async {
+                    Ok(vec![
+                        cash_flow("SALARY", 0.0, 30000.0),
+                        cash_flow("FOOD", -2500.0, 0.0),
+                        cash_flow("COFFEE", -500.0, 0.0),
+                    ])
+                })
+            });
+
+        EnvelopeUseCase::new(
+            Arc::new(mock_envelope_repository),
+            Arc::new(mock_spending_scanner_repository),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_status_success() {
+        let envelope_use_case = envelope_use_case(MockEnvelopeRepository::new());
+
+        let result = envelope_use_case.status().await;
+
+        assert!(result.is_ok());
+
+        let status = result.unwrap();
+
+        assert_eq!(status.income, 30000.0);
+        assert_eq!(status.unenveloped_spent, 500.0);
+        assert_eq!(status.unassigned, 9500.0);
+        assert_eq!(status.envelopes.len(), 2);
+        assert_eq!(status.envelopes[0].envelope, "FOOD");
+        assert_eq!(status.envelopes[0].assigned, 9000.0);
+        assert_eq!(status.envelopes[0].available, 6500.0);
+        assert_eq!(status.envelopes[1].envelope, "RENT");
+        assert_eq!(status.envelopes[1].available, 11000.0);
+    }
+
+    #[tokio::test]
+    async fn test_assign_and_move_check_balances() {
+        let mut mock_envelope_repository = MockEnvelopeRepository::new();
+
+        // The repository refuses what the source cannot cover, the status explains why
+        mock_envelope_repository
+            .expect_add_transfer()
+            .times(6)
+            .returning(|dto| {
+                let available = match dto.from_envelope.as_deref() {
+                    None => 9500.0,
+                    Some("FOOD") => 6500.0,
+                    Some("RENT") => 11000.0,
+                    _ => 0.0,
+                };
+                Box::pin(async move { Ok((dto.amount <= available).then_some(4)) })
+            });
+
+        let envelope_use_case = envelope_use_case(mock_envelope_repository);
+
+        let assign = |envelope: &str, amount: f32| {
+            envelope_use_case.assign(AssignEnvelopeModel {
+                envelope: envelope.to_string(),
+                amount,
+            })
+        };
+        let move_between = |from: &str, to: &str, amount: f32| {
+            envelope_use_case.move_between(MoveEnvelopeModel {
+                from_envelope: from.to_string(),
+                to_envelope: to.to_string(),
+                amount,
+            })
+        };
+
+        assert!(assign("coffee", 9500.0).await.is_ok());
+        let refused = assign("coffee", 9500.01).await.unwrap_err();
+        assert!(refused.to_string().contains("only 9500.00 is unassigned"));
+        assert!(assign("coffee", 0.0).await.is_err());
+
+        let unassign = |envelope: &str, amount: f32| {
+            envelope_use_case.unassign(AssignEnvelopeModel {
+                envelope: envelope.to_string(),
+                amount,
+            })
+        };
+
+        assert!(unassign("food", 6500.0).await.is_ok());
+        let refused = unassign("rent", 11000.01).await.unwrap_err();
+        assert!(refused.to_string().contains("only 11000.00 is available"));
+
+        assert!(move_between("food", "coffee", 6500.0).await.is_ok());
+        let refused = move_between("food", "coffee", 6500.01).await.unwrap_err();
+        assert!(refused.to_string().contains("only 6500.00 is available"));
+        assert!(move_between("food", "FOOD", 1.0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_status_leaves_earlier_spending_in_the_pool() {
+        let status_with = |transfers: Vec<EnvelopeTransfer>| {
+            let mut mock_envelope_repository = MockEnvelopeRepository::new();
+            let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+            mock_envelope_repository
+                .expect_view_all_transfers()
+                .returning(move || {
+                    let transfers = transfers.clone();
+                    Box::pin(async move { Ok(transfers) })
+                });
+
+            // FOOD was spent before the envelope existed, nothing since
+            mock_spending_scanner_repository
+                .expect_cash_flow_by_category()
+                .returning(|start, _| {
+                    Box::pin(async move {
+                        match start == LIFETIME_START {
+                            true => Ok(vec![
+                                cash_flow("SALARY", 0.0, 30000.0),
+                                cash_flow("FOOD", -10000.0, 0.0),
+                            ]),
+                            false => Ok(vec![]),
+                        }
+                    })
+                });
+
+            EnvelopeUseCase::new(
+                Arc::new(mock_envelope_repository),
+                Arc::new(mock_spending_scanner_repository),
+            )
+        };
+
+        let before = status_with(vec![]).status().await.unwrap();
+        let after = status_with(vec![transfer(1, None, Some("FOOD"), 5000.0)])
+            .status()
+            .await
+            .unwrap();
+
+        assert_eq!(before.unassigned, 20000.0);
+        assert_eq!(after.unassigned, 15000.0);
+        assert_eq!(after.unenveloped_spent, 10000.0);
+        assert_eq!(after.envelopes[0].spent, 0.0);
+        assert_eq!(after.envelopes[0].available, 5000.0);
+    }
+}
@@ -1,4 +1,9 @@
+pub mod bills;
+pub mod budget;
 pub mod cash_flow;
+pub mod envelope;
 
 pub mod spending_scanner;
+pub mod subscription;
 pub mod tax_simulator;
+pub mod unusual_spending;
@@ -1,14 +1,37 @@
-use anyhow::Result;
-use std::{collections::HashMap, sync::Arc};
-
-use crate::domain::{
-    repositories::spending_scanner::SpendingScannerRepository,
-    value_objects::spending_scanner::{
-        AddMonthlySpendingModel, MonthlySpendingModel, Range, RemoveMonthlySpendingModel,
-        SpendingScannerFilter, SpendingScannerModel,
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Local, NaiveDate};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Arc,
+};
+
+use crate::{
+    application::statistics::{mean, median, percentile, std_dev},
+    domain::{
+        entities::{monthly_spending::AddMonthlySpendingDto, my_ledger::MyLedger},
+        repositories::spending_scanner::SpendingScannerRepository,
+        value_objects::{
+            recurrence::RecurrenceModel,
+            spending_scanner::{
+                AddMonthlySpendingModel, CategoryBreakdownModel, CategoryComparisonModel,
+                CategoryStatisticsModel, CompareSpendingModel, MonthlySpendingModel,
+                MonthlySpendingOccurrenceModel, Range, RemoveMonthlySpendingModel,
+                SearchTransactionsModel, SpendingBreakdownModel, SpendingBucketModel,
+                SpendingComparisonModel, SpendingPeriodModel, SpendingScannerFilter,
+                SpendingScannerModel, SpendingTimeSeriesFilter, TopSpendingFilter,
+                TopSpendingModel, TransactionMatchModel, UpdateMonthlySpendingModel,
+            },
+            validation::ValidationErrors,
+        },
     },
 };
 
+const BIGGEST_MOVERS_LIMIT: usize = 3;
+const DEFAULT_TOP_LIMIT: i64 = 10;
+pub(crate) const LIFETIME_START: &str = "0001-01-01";
+/// Exclusive, like every end bound handed to the repository.
+pub(crate) const LIFETIME_END: &str = "9999-12-31";
+
 #[derive(Clone)]
 pub struct SpendingScannerUseCase {
     spending_scanner_repository: Arc<dyn SpendingScannerRepository + Send + Sync + 'static>,
@@ -27,15 +50,7 @@ impl SpendingScannerUseCase {
         &self,
         spending_scanner_filer: SpendingScannerFilter,
     ) -> Result<Vec<SpendingScannerModel>> {
-        let results = match spending_scanner_filer.filter {
-            Range::Today => self.spending_scanner_repository.today().await?,
-            Range::ThisMonth => self.spending_scanner_repository.this_month().await?,
-            Range::ThisYear => self.spending_scanner_repository.this_year().await?,
-            Range::Lifetime => self.spending_scanner_repository.lifetime().await?,
-            Range::Custom { start, end } => {
-                self.spending_scanner_repository.custom(start, end).await?
-            }
-        };
+        let results = self.load_range(spending_scanner_filer.filter).await?;
 
         Ok(results
             .iter()
@@ -47,30 +62,262 @@ impl SpendingScannerUseCase {
         &self,
         spending_scanner_filer: SpendingScannerFilter,
     ) -> Result<HashMap<String, f32>> {
-        let results = match spending_scanner_filer.filter {
-            Range::Today => self.spending_scanner_repository.today().await?,
-            Range::ThisMonth => self.spending_scanner_repository.this_month().await?,
-            Range::ThisYear => self.spending_scanner_repository.this_year().await?,
-            Range::Lifetime => self.spending_scanner_repository.lifetime().await?,
-            Range::Custom { start, end } => {
-                self.spending_scanner_repository.custom(start, end).await?
+        let (start, end) = range_dates(&spending_scanner_filer.filter)?;
+
+        let totals = self
+            .spending_scanner_repository
+            .sum_by_category(start, end)
+            .await?;
+
+        Ok(totals
+            .into_iter()
+            .map(|t| (t.category, t.total.unwrap_or(0.0)))
+            .collect())
+    }
+
+    /// Income and expenses per category kept apart, largest spending first.
+    pub async fn breakdown(
+        &self,
+        spending_scanner_filer: SpendingScannerFilter,
+    ) -> Result<SpendingBreakdownModel> {
+        let (start, end) = range_dates(&spending_scanner_filer.filter)?;
+
+        let cash_flows = self
+            .spending_scanner_repository
+            .cash_flow_by_category(start, end)
+            .await?;
+
+        let expense = cash_flows
+            .iter()
+            .map(|c| -c.expense.unwrap_or(0.0))
+            .sum::<f32>();
+        let income = cash_flows
+            .iter()
+            .map(|c| c.income.unwrap_or(0.0))
+            .sum::<f32>();
+
+        let mut categories = cash_flows
+            .into_iter()
+            .map(|c| {
+                let category_expense = -c.expense.unwrap_or(0.0);
+                let category_income = c.income.unwrap_or(0.0);
+
+                CategoryBreakdownModel {
+                    category: c.category,
+                    expense: category_expense,
+                    income: category_income,
+                    net: category_income - category_expense,
+                    share_of_spending: match expense > 0.0 {
+                        true => category_expense / expense * 100.0,
+                        false => 0.0,
+                    },
+                }
+            })
+            .collect::<Vec<CategoryBreakdownModel>>();
+        categories.sort_by(|a, b| {
+            b.expense
+                .total_cmp(&a.expense)
+                .then(b.income.total_cmp(&a.income))
+                .then(a.category.cmp(&b.category))
+        });
+
+        Ok(SpendingBreakdownModel {
+            income,
+            expense,
+            net: income - expense,
+            categories,
+        })
+    }
+
+    /// Spread of expenses per category, sorted by total spend. Expenses are reported as positive amounts.
+    pub async fn statistics(
+        &self,
+        spending_scanner_filer: SpendingScannerFilter,
+    ) -> Result<Vec<CategoryStatisticsModel>> {
+        let results = self.load_range(spending_scanner_filer.filter).await?;
+
+        let expenses_by_category = results.iter().filter(|r| r.amount < 0.0).fold(
+            HashMap::new(),
+            |mut acc: HashMap<String, Vec<f32>>, r| {
+                acc.entry(r.category.clone()).or_default().push(-r.amount);
+                acc
+            },
+        );
+
+        let mut statistics = expenses_by_category
+            .into_iter()
+            .filter_map(|(category, spent)| {
+                Some(CategoryStatisticsModel {
+                    count: spent.len(),
+                    total: spent.iter().sum(),
+                    mean: mean(&spent)?,
+                    median: median(&spent)?,
+                    p90: percentile(&spent, 90.0)?,
+                    min: spent.iter().copied().reduce(f32::min)?,
+                    max: spent.iter().copied().reduce(f32::max)?,
+                    std_dev: std_dev(&spent)?,
+                    category,
+                })
+            })
+            .collect::<Vec<CategoryStatisticsModel>>();
+
+        statistics.sort_by(|a, b| b.total.total_cmp(&a.total));
+
+        Ok(statistics)
+    }
+
+    pub async fn time_series(
+        &self,
+        spending_time_series_filter: SpendingTimeSeriesFilter,
+    ) -> Result<Vec<SpendingBucketModel>> {
+        let results = self.load_range(spending_time_series_filter.filter).await?;
+
+        let group_by = spending_time_series_filter.group_by;
+
+        let buckets = results.iter().fold(BTreeMap::new(), |mut acc, r| {
+            let Some(date) = r.naive_date() else {
+                return acc;
+            };
+
+            let (start, end) = group_by.bucket_bounds(date);
+            let bucket = acc.entry(start).or_insert_with(|| SpendingBucketModel {
+                period: group_by.label(start),
+                start: start.to_string(),
+                end: end.to_string(),
+                total: 0.0,
+                categories: BTreeMap::new(),
+                expense: 0.0,
+                expenses: BTreeMap::new(),
+            });
+
+            bucket.total += r.amount;
+            *bucket.categories.entry(r.category.clone()).or_insert(0.0) += r.amount;
+
+            if r.amount < 0.0 {
+                bucket.expense -= r.amount;
+                *bucket.expenses.entry(r.category.clone()).or_insert(0.0) -= r.amount;
             }
+
+            acc
+        });
+
+        Ok(buckets.into_values().collect())
+    }
+
+    pub async fn compare(
+        &self,
+        compare_spending_model: CompareSpendingModel,
+    ) -> Result<SpendingComparisonModel> {
+        let today = Local::now().date_naive();
+
+        let current_range = compare_spending_model.current;
+        let previous_range = match compare_spending_model.previous {
+            Some(previous) => previous,
+            None => current_range.previous_period(today).ok_or_else(|| {
+                anyhow!(
+                    "{:?} has no previous period, please give one",
+                    current_range
+                )
+            })?,
         };
 
-        let map_by_category =
-            results
+        let current = cash_flow_by_category(&self.load_range(current_range.clone()).await?);
+        let previous = cash_flow_by_category(&self.load_range(previous_range.clone()).await?);
+
+        let categories = current
+            .keys()
+            .chain(previous.keys())
+            .collect::<BTreeSet<&String>>()
+            .into_iter()
+            .map(|category| {
+                let (current_expense, current_income) =
+                    current.get(category).copied().unwrap_or_default();
+                let (previous_expense, previous_income) =
+                    previous.get(category).copied().unwrap_or_default();
+                let delta = current_expense - previous_expense;
+
+                CategoryComparisonModel {
+                    category: category.to_owned(),
+                    current: current_expense,
+                    previous: previous_expense,
+                    delta,
+                    delta_percent: match previous_expense == 0.0 {
+                        true => None,
+                        _ => Some(delta / previous_expense * 100.0),
+                    },
+                    current_income,
+                    previous_income,
+                }
+            })
+            .collect::<Vec<CategoryComparisonModel>>();
+
+        let mut biggest_movers = categories
+            .iter()
+            .filter(|c| c.delta != 0.0)
+            .cloned()
+            .collect::<Vec<CategoryComparisonModel>>();
+        biggest_movers.sort_by(|a, b| b.delta.abs().total_cmp(&a.delta.abs()));
+        biggest_movers.truncate(BIGGEST_MOVERS_LIMIT);
+
+        Ok(SpendingComparisonModel {
+            current: period_model(&current_range, today, &current),
+            previous: period_model(&previous_range, today, &previous),
+            categories,
+            biggest_movers,
+        })
+    }
+
+    pub async fn top_spending(
+        &self,
+        top_spending_filter: TopSpendingFilter,
+    ) -> Result<TopSpendingModel> {
+        let (start, end) = range_dates(&top_spending_filter.filter)?;
+        let limit = top_spending_filter
+            .limit
+            .unwrap_or(DEFAULT_TOP_LIMIT)
+            .max(1);
+
+        let largest_transactions = self
+            .spending_scanner_repository
+            .top_expenses(start.clone(), end.clone(), limit)
+            .await?;
+
+        let top_merchants = self
+            .spending_scanner_repository
+            .top_merchants(start, end, limit, top_spending_filter.rank_merchants_by)
+            .await?;
+
+        Ok(TopSpendingModel {
+            largest_transactions: largest_transactions
                 .iter()
-                .fold(std::collections::HashMap::new(), |mut acc, r| {
-                    let category = r.category.clone();
-                    let amount = r.amount;
+                .map(|r| r.to_spending_scanner_model())
+                .collect(),
+            top_merchants: top_merchants.iter().map(|m| m.to_model()).collect(),
+        })
+    }
 
-                    acc.entry(category.clone()).or_insert(0.0);
-                    *acc.get_mut(&category).unwrap() += amount;
+    pub async fn search_transactions(
+        &self,
+        search_transactions_model: SearchTransactionsModel,
+    ) -> Result<Vec<TransactionMatchModel>> {
+        let (start, end) =
+            range_dates(&search_transactions_model.filter.unwrap_or(Range::Lifetime))?;
 
-                    acc
-                });
+        let matches = self
+            .spending_scanner_repository
+            .search(
+                search_transactions_model.query,
+                start,
+                end,
+                search_transactions_model.category.map(|c| c.to_uppercase()),
+                search_transactions_model
+                    .limit
+                    .unwrap_or(DEFAULT_TOP_LIMIT)
+                    .max(1),
+            )
+            .await?;
 
-        Ok(map_by_category)
+        Ok(matches.iter().map(|m| m.to_model()).collect())
     }
 
     pub async fn view_all_monthly_spending_list(&self) -> Result<Vec<MonthlySpendingModel>> {
@@ -91,11 +338,152 @@ impl SpendingScannerUseCase {
         &self,
         add_monthly_spending_model: AddMonthlySpendingModel,
     ) -> Result<i32> {
+        let today = Local::now().date_naive();
+
+        let mut errors = ValidationErrors::default();
+        check_monthly_spending(
+            &add_monthly_spending_model.title,
+            add_monthly_spending_model.amount,
+            add_monthly_spending_model.category.as_deref(),
+            &mut errors,
+        );
+        let recurrence = resolve_recurrence(
+            &add_monthly_spending_model.recurrence,
+            &add_monthly_spending_model.due_date,
+            today,
+            &mut errors,
+        );
+        if recurrence.is_none()
+            && add_monthly_spending_model.recurrence.is_none()
+            && add_monthly_spending_model.due_date.is_none()
+        {
+            errors.add(
+                "recurrence",
+                "Either recurrence or due_date is required".to_string(),
+            );
+        }
+        errors.into_result()?;
+
+        let recurrence = recurrence.ok_or_else(|| anyhow!("No recurrence resolved"))?;
+
         self.spending_scanner_repository
-            .add_monthly_spending(add_monthly_spending_model.to_dto())
+            .add_monthly_spending(add_monthly_spending_model.to_dto(&recurrence, today))
             .await
     }
 
+    /// Changes the given fields of a scheduled payment. A new schedule only posts and tracks
+    /// cycles from today on, upcoming pending cycles of the old one are dropped while overdue
+    /// ones stay until they are paid or skipped.
+    pub async fn update_monthly_spending(
+        &self,
+        update_monthly_spending_model: UpdateMonthlySpendingModel,
+    ) -> Result<MonthlySpendingModel> {
+        let today = Local::now().date_naive();
+        let id = update_monthly_spending_model.id;
+
+        let existing = self
+            .spending_scanner_repository
+            .view_all_monthly_spending()
+            .await?
+            .into_iter()
+            .find(|item| item.id == id)
+            .ok_or_else(|| anyhow!("No monthly spending with id {}", id))?;
+
+        let title = update_monthly_spending_model
+            .title
+            .unwrap_or(existing.title.to_owned());
+        let amount = update_monthly_spending_model
+            .amount
+            .unwrap_or(existing.amount);
+        let category = update_monthly_spending_model
+            .category
+            .map(|c| c.to_uppercase())
+            .unwrap_or(existing.category.to_owned());
+        let posting = update_monthly_spending_model
+            .posting
+            .unwrap_or(existing.posting_mode());
+
+        let mut errors = ValidationErrors::default();
+        check_monthly_spending(&title, amount, Some(&category), &mut errors);
+        let recurrence = resolve_recurrence(
+            &update_monthly_spending_model.recurrence,
+            &update_monthly_spending_model.due_date,
+            today,
+            &mut errors,
+        )
+        .unwrap_or(existing.recurrence());
+        errors.into_result()?;
+
+        let reschedule_from = (recurrence != existing.recurrence()).then(|| today.to_string());
+        let post_from = reschedule_from
+            .to_owned()
+            .unwrap_or(existing.post_from.to_owned());
+
+        self.spending_scanner_repository
+            .update_monthly_spending(
+                id,
+                AddMonthlySpendingDto {
+                    title: title.to_owned(),
+                    amount,
+                    frequency: recurrence.frequency.as_str().to_string(),
+                    interval: recurrence.interval as i32,
+                    anchor_date: recurrence.anchor_date.to_owned(),
+                    end_date: recurrence.end_date.to_owned(),
+                    count: recurrence.count.map(|c| c as i32),
+                    category: category.to_owned(),
+                    posting: posting.as_str().to_string(),
+                    post_from,
+                },
+                reschedule_from,
+            )
+            .await?;
+
+        Ok(MonthlySpendingModel {
+            id,
+            title,
+            amount,
+            recurrence,
+            category,
+            posting,
+        })
+    }
+
+    /// Every scheduled payment that falls in the range, in date order.
+    pub async fn monthly_spending_occurrences(
+        &self,
+        spending_scanner_filer: SpendingScannerFilter,
+    ) -> Result<Vec<MonthlySpendingOccurrenceModel>> {
+        let (start, end) = spending_scanner_filer
+            .filter
+            .bounds(Local::now().date_naive())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Occurrences need a bounded date range, got {:?}",
+                    spending_scanner_filer.filter
+                )
+            })?;
+
+        let entities = self
+            .spending_scanner_repository
+            .view_all_monthly_spending()
+            .await?;
+
+        let mut results = Vec::new();
+        for entity in &entities {
+            for date in entity.recurrence().occurrences(start, end)? {
+                results.push(MonthlySpendingOccurrenceModel {
+                    id: entity.id,
+                    title: entity.title.to_owned(),
+                    amount: entity.amount,
+                    date: date.to_string(),
+                });
+            }
+        }
+        results.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+
+        Ok(results)
+    }
+
     pub async fn remove_monthly_spending(
         &self,
         remove_monthly_spending_model: RemoveMonthlySpendingModel,
@@ -104,6 +492,128 @@ impl SpendingScannerUseCase {
             .remove_monthly_spending(remove_monthly_spending_model.id)
             .await
     }
+
+    async fn load_range(&self, range: Range) -> Result<Vec<MyLedger>> {
+        match range {
+            Range::Today => self.spending_scanner_repository.today().await,
+            Range::ThisMonth => self.spending_scanner_repository.this_month().await,
+            Range::ThisYear => self.spending_scanner_repository.this_year().await,
+            Range::Lifetime => self.spending_scanner_repository.lifetime().await,
+            Range::Custom { .. } => {
+                let (start, end) = range_dates(&range)?;
+                self.spending_scanner_repository.custom(start, end).await
+            }
+        }
+    }
+}
+
+/// Exclusive end bound for a range whose last day is `date`.
+pub(crate) fn day_after(date: NaiveDate) -> String {
+    date.succ_opt()
+        .filter(|next| next.year() <= 9999)
+        .map_or(LIFETIME_END.to_string(), |next| next.to_string())
+}
+
+/// Half-open `[start, end)` bounds of a range as the database stores them.
+fn range_dates(range: &Range) -> Result<(String, String)> {
+    match range {
+        Range::Lifetime => Ok((LIFETIME_START.to_string(), LIFETIME_END.to_string())),
+        _ => range
+            .bounds(Local::now().date_naive())
+            .map(|(start, end)| (start.to_string(), day_after(end)))
+            .ok_or_else(|| anyhow!("Invalid date range, expected YYYY-MM-DD: {:?}", range)),
+    }
+}
+
+/// (Expense, Income) per category, expenses as positive spending.
+fn cash_flow_by_category(results: &[MyLedger]) -> HashMap<String, (f32, f32)> {
+    results.iter().fold(HashMap::new(), |mut acc, r| {
+        let (expense, income) = acc.entry(r.category.clone()).or_insert((0.0, 0.0));
+        match r.amount < 0.0 {
+            true => *expense -= r.amount,
+            false => *income += r.amount,
+        }
+        acc
+    })
+}
+
+fn period_model(
+    range: &Range,
+    today: NaiveDate,
+    by_category: &HashMap<String, (f32, f32)>,
+) -> SpendingPeriodModel {
+    let bounds = range.bounds(today);
+    let expense = by_category.values().map(|(e, _)| e).sum::<f32>();
+    let income = by_category.values().map(|(_, i)| i).sum::<f32>();
+
+    SpendingPeriodModel {
+        start: bounds.map(|(start, _)| start.to_string()),
+        end: bounds.map(|(_, end)| end.to_string()),
+        expense,
+        income,
+        net: income - expense,
+    }
+}
+
+/// The explicit recurrence, or monthly on `due_date`, `None` when neither is given or valid.
+fn resolve_recurrence(
+    recurrence: &Option<RecurrenceModel>,
+    due_date: &Option<String>,
+    today: NaiveDate,
+    errors: &mut ValidationErrors,
+) -> Option<RecurrenceModel> {
+    match (recurrence, due_date) {
+        (Some(recurrence), _) => {
+            recurrence.check(errors);
+            Some(recurrence.clone())
+        }
+        (None, Some(due_date)) => {
+            let day = match due_date.trim().to_lowercase().as_str() {
+                // Anchored on a 31st, every month falls back to its last day
+                "last" => Some(31),
+                day => day.parse::<u32>().ok().filter(|d| (1..=31).contains(d)),
+            };
+
+            match day.map(|day| RecurrenceModel::monthly_on_day(day, today)) {
+                Some(Ok(recurrence)) => Some(recurrence),
+                Some(Err(e)) => {
+                    errors.add("due_date", e.to_string());
+                    None
+                }
+                None => {
+                    errors.add(
+                        "due_date",
+                        format!(
+                            "Expected a day of the month from 1 to 31, or \"last\", got {:?}",
+                            due_date
+                        ),
+                    );
+                    None
+                }
+            }
+        }
+        (None, None) => None,
+    }
+}
+
+fn check_monthly_spending(
+    title: &str,
+    amount: f32,
+    category: Option<&str>,
+    errors: &mut ValidationErrors,
+) {
+    if title.trim().is_empty() {
+        errors.add("title", "Must not be empty".to_string());
+    }
+    if !amount.is_finite() || amount <= 0.0 {
+        errors.add(
+            "amount",
+            format!("Must be a positive amount, got {}", amount),
+        );
+    }
+    if category.is_some_and(|c| c.trim().is_empty()) {
+        errors.add("category", "Must not be empty when given".to_string());
+    }
 }
 
 #[cfg(test)]
@@ -113,10 +623,22 @@ mod tests {
     use crate::{
         application::use_cases::spending_scanner::SpendingScannerUseCase,
         domain::{
-            entities::{monthly_spending::MonthlySpending, my_ledger::MyLedger},
+            entities::{
+                monthly_spending::MonthlySpending,
+                my_ledger::{
+                    CategoryCashFlow, CategoryTotal, MerchantSummary, MyLedger, TransactionMatch,
+                },
+            },
             repositories::spending_scanner::MockSpendingScannerRepository,
-            value_objects::spending_scanner::{
-                AddMonthlySpendingModel, Range, RemoveMonthlySpendingModel, SpendingScannerFilter,
+            value_objects::{
+                bills::PostingMode,
+                spending_scanner::{
+                    AddMonthlySpendingModel, CompareSpendingModel, Grouping, MerchantRanking,
+                    Range, RemoveMonthlySpendingModel, SearchTransactionsModel,
+                    SpendingScannerFilter, SpendingTimeSeriesFilter, TopSpendingFilter,
+                    UpdateMonthlySpendingModel,
+                },
+                validation::ValidationErrors,
             },
         },
     };
@@ -175,7 +697,185 @@ mod tests {
         let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
 
         mock_spending_scanner_repository
-            .expect_today()
+            .expect_sum_by_category()
+            .returning(|_, _| {
+                Box::pin(async {
+                    Ok(vec![
+                        CategoryTotal {
+                            category: "Food".to_string(),
+                            total: Some(300.0),
+                        },
+                        CategoryTotal {
+                            category: "Coffee".to_string(),
+                            total: Some(150.0),
+                        },
+                    ])
+                })
+            });
+
+        let spending_scanner_use_case =
+            SpendingScannerUseCase::new(Arc::new(mock_spending_scanner_repository));
+
+        let spending_scanner_filter = SpendingScannerFilter {
+            filter: Range::Today,
+        };
+
+        let result = spending_scanner_use_case
+            .visualize(spending_scanner_filter)
+            .await;
+
+        assert!(result.is_ok());
+
+        let lunch = result.as_ref().unwrap().get("Food").unwrap();
+        let coffee = result.as_ref().unwrap().get("Coffee").unwrap();
+
+        assert_eq!(*lunch, 300.0);
+        assert_eq!(*coffee, 150.0);
+    }
+
+    #[tokio::test]
+    async fn test_breakdown_success() {
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_spending_scanner_repository
+            .expect_cash_flow_by_category()
+            .returning(|_, _| {
+                Box::pin(async {
+                    Ok(vec![
+                        CategoryCashFlow {
+                            category: "SHOPPING".to_string(),
+                            expense: Some(-1000.0),
+                            income: Some(400.0),
+                        },
+                        CategoryCashFlow {
+                            category: "SALARY".to_string(),
+                            expense: Some(0.0),
+                            income: Some(30000.0),
+                        },
+                        CategoryCashFlow {
+                            category: "FOOD".to_string(),
+                            expense: Some(-3000.0),
+                            income: Some(0.0),
+                        },
+                    ])
+                })
+            });
+
+        let spending_scanner_use_case =
+            SpendingScannerUseCase::new(Arc::new(mock_spending_scanner_repository));
+
+        let spending_scanner_filter = SpendingScannerFilter {
+            filter: Range::ThisMonth,
+        };
+
+        let result = spending_scanner_use_case
+            .breakdown(spending_scanner_filter)
+            .await;
+
+        assert!(result.is_ok());
+
+        let breakdown = result.unwrap();
+
+        assert_eq!(breakdown.income, 30400.0);
+        assert_eq!(breakdown.expense, 4000.0);
+        assert_eq!(breakdown.net, 26400.0);
+        assert_eq!(
+            breakdown
+                .categories
+                .iter()
+                .map(|c| c.category.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["FOOD", "SHOPPING", "SALARY"]
+        );
+        assert_eq!(breakdown.categories[0].share_of_spending, 75.0);
+        assert_eq!(breakdown.categories[1].expense, 1000.0);
+        assert_eq!(breakdown.categories[1].income, 400.0);
+        assert_eq!(breakdown.categories[1].net, -600.0);
+        assert_eq!(breakdown.categories[2].share_of_spending, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_statistics_success() {
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_spending_scanner_repository
+            .expect_this_month()
+            .returning(|| {
+                Box::pin(async {
+                    Ok(vec![
+                        MyLedger {
+                            id: 1,
+                            amount: -100.0,
+                            category: "FOOD".to_string(),
+                            date: "2025-04-01".to_string(),
+                            description: "Lunch".to_string(),
+                        },
+                        MyLedger {
+                            id: 2,
+                            amount: -300.0,
+                            category: "FOOD".to_string(),
+                            date: "2025-04-02".to_string(),
+                            description: "Dinner".to_string(),
+                        },
+                        MyLedger {
+                            id: 3,
+                            amount: -200.0,
+                            category: "FOOD".to_string(),
+                            date: "2025-04-03".to_string(),
+                            description: "Lunch".to_string(),
+                        },
+                        MyLedger {
+                            id: 4,
+                            amount: -1000.0,
+                            category: "SHOPPING".to_string(),
+                            date: "2025-04-03".to_string(),
+                            description: "Shoes".to_string(),
+                        },
+                        MyLedger {
+                            id: 5,
+                            amount: 50_000.0,
+                            category: "SALARY".to_string(),
+                            date: "2025-04-01".to_string(),
+                            description: "Salary".to_string(),
+                        },
+                    ])
+                })
+            });
+
+        let spending_scanner_use_case =
+            SpendingScannerUseCase::new(Arc::new(mock_spending_scanner_repository));
+
+        let result = spending_scanner_use_case
+            .statistics(SpendingScannerFilter {
+                filter: Range::ThisMonth,
+            })
+            .await;
+
+        assert!(result.is_ok());
+
+        let statistics = result.unwrap();
+
+        assert_eq!(statistics.len(), 2);
+        assert_eq!(statistics[0].category, "SHOPPING");
+
+        let food = &statistics[1];
+
+        assert_eq!(food.count, 3);
+        assert_eq!(food.total, 600.0);
+        assert_eq!(food.mean, 200.0);
+        assert_eq!(food.median, 200.0);
+        assert_eq!(food.p90, 280.0);
+        assert_eq!(food.min, 100.0);
+        assert_eq!(food.max, 300.0);
+        assert!((food.std_dev - 81.649_66).abs() < 0.001);
+    }
+
+    #[tokio::test]
+    async fn test_time_series_success() {
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_spending_scanner_repository
+            .expect_this_year()
             .returning(|| {
                 Box::pin(async {
                     Ok(vec![
@@ -183,21 +883,21 @@ mod tests {
                             id: 1,
                             amount: 100.0,
                             category: "Food".to_string(),
-                            date: "2023-10-01".to_string(),
+                            date: "2025-03-02".to_string(),
                             description: "Lunch".to_string(),
                         },
                         MyLedger {
                             id: 2,
                             amount: 200.0,
                             category: "Food".to_string(),
-                            date: "2023-10-01".to_string(),
-                            description: "Lunch".to_string(),
+                            date: "2025-01-15".to_string(),
+                            description: "Dinner".to_string(),
                         },
                         MyLedger {
                             id: 3,
                             amount: 150.0,
                             category: "Coffee".to_string(),
-                            date: "2023-10-01".to_string(),
+                            date: "2025-01-03".to_string(),
                             description: "Coffee".to_string(),
                         },
                     ])
@@ -207,21 +907,232 @@ mod tests {
         let spending_scanner_use_case =
             SpendingScannerUseCase::new(Arc::new(mock_spending_scanner_repository));
 
-        let spending_scanner_filter = SpendingScannerFilter {
-            filter: Range::Today,
+        let spending_time_series_filter = SpendingTimeSeriesFilter {
+            filter: Range::ThisYear,
+            group_by: Grouping::Month,
         };
 
         let result = spending_scanner_use_case
-            .visualize(spending_scanner_filter)
+            .time_series(spending_time_series_filter)
             .await;
 
         assert!(result.is_ok());
 
-        let lunch = result.as_ref().unwrap().get("Food").unwrap();
-        let coffee = result.as_ref().unwrap().get("Coffee").unwrap();
+        let buckets = result.unwrap();
 
-        assert_eq!(*lunch, 300.0);
-        assert_eq!(*coffee, 150.0);
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].period, "2025-01");
+        assert_eq!(buckets[0].end, "2025-01-31");
+        assert_eq!(buckets[0].total, 350.0);
+        assert_eq!(*buckets[0].categories.get("Coffee").unwrap(), 150.0);
+        assert_eq!(buckets[1].period, "2025-03");
+        assert_eq!(*buckets[1].categories.get("Food").unwrap(), 100.0);
+    }
+
+    #[tokio::test]
+    async fn test_compare_success() {
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_spending_scanner_repository
+            .expect_custom()
+            .returning(|start, _| {
+                Box::pin(async move {
+                    match start.as_str() {
+                        "2025-02-01" => Ok(vec![
+                            MyLedger {
+                                id: 3,
+                                amount: -300.0,
+                                category: "Food".to_string(),
+                                date: "2025-02-10".to_string(),
+                                description: "Dinner".to_string(),
+                            },
+                            MyLedger {
+                                id: 4,
+                                amount: -50.0,
+                                category: "Coffee".to_string(),
+                                date: "2025-02-11".to_string(),
+                                description: "Coffee".to_string(),
+                            },
+                            MyLedger {
+                                id: 5,
+                                amount: 30000.0,
+                                category: "Salary".to_string(),
+                                date: "2025-02-25".to_string(),
+                                description: "Salary".to_string(),
+                            },
+                        ]),
+                        _ => Ok(vec![
+                            MyLedger {
+                                id: 1,
+                                amount: -200.0,
+                                category: "Food".to_string(),
+                                date: "2025-01-10".to_string(),
+                                description: "Lunch".to_string(),
+                            },
+                            MyLedger {
+                                id: 2,
+                                amount: -120.0,
+                                category: "Transport".to_string(),
+                                date: "2025-01-11".to_string(),
+                                description: "Taxi".to_string(),
+                            },
+                        ]),
+                    }
+                })
+            });
+
+        let spending_scanner_use_case =
+            SpendingScannerUseCase::new(Arc::new(mock_spending_scanner_repository));
+
+        let result = spending_scanner_use_case
+            .compare(CompareSpendingModel {
+                current: Range::Custom {
+                    start: "2025-02-01".to_string(),
+                    end: "2025-02-28".to_string(),
+                },
+                previous: None,
+            })
+            .await;
+
+        assert!(result.is_ok());
+
+        let comparison = result.unwrap();
+
+        assert_eq!(comparison.previous.start.as_deref(), Some("2025-01-04"));
+        assert_eq!(comparison.previous.end.as_deref(), Some("2025-01-31"));
+        assert_eq!(comparison.current.expense, 350.0);
+        assert_eq!(comparison.current.income, 30000.0);
+        assert_eq!(comparison.previous.expense, 320.0);
+        assert_eq!(comparison.categories.len(), 4);
+        // Salary moves no spending
+        assert_eq!(comparison.biggest_movers.len(), 3);
+        assert_eq!(comparison.biggest_movers[0].category, "Transport");
+        assert_eq!(comparison.biggest_movers[0].delta, -120.0);
+        assert_eq!(comparison.biggest_movers[0].delta_percent, Some(-100.0));
+
+        let food = comparison
+            .categories
+            .iter()
+            .find(|c| c.category == "Food")
+            .unwrap();
+
+        assert_eq!(food.delta, 100.0);
+        assert_eq!(food.delta_percent, Some(50.0));
+    }
+
+    #[tokio::test]
+    async fn test_top_spending_success() {
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_spending_scanner_repository
+            .expect_top_expenses()
+            .withf(|start, end, limit| start == "0001-01-01" && end == "9999-12-31" && *limit == 2)
+            .returning(|_, _, _| {
+                Box::pin(async {
+                    Ok(vec![MyLedger {
+                        id: 1,
+                        amount: -1500.0,
+                        category: "Shopping".to_string(),
+                        date: "2025-03-02".to_string(),
+                        description: "Shoes".to_string(),
+                    }])
+                })
+            });
+
+        mock_spending_scanner_repository
+            .expect_top_merchants()
+            .withf(|_, _, _, rank_by| *rank_by == MerchantRanking::Frequency)
+            .returning(|_, _, _, _| {
+                Box::pin(async {
+                    Ok(vec![MerchantSummary {
+                        description: "KFC".to_string(),
+                        count: 4,
+                        total: Some(-516.0),
+                        average: Some(-129.0),
+                    }])
+                })
+            });
+
+        let spending_scanner_use_case =
+            SpendingScannerUseCase::new(Arc::new(mock_spending_scanner_repository));
+
+        let result = spending_scanner_use_case
+            .top_spending(TopSpendingFilter {
+                filter: Range::Lifetime,
+                limit: Some(2),
+                rank_merchants_by: MerchantRanking::Frequency,
+            })
+            .await;
+
+        assert!(result.is_ok());
+
+        let top_spending = result.unwrap();
+
+        assert_eq!(top_spending.largest_transactions.len(), 1);
+        assert_eq!(top_spending.top_merchants[0].count, 4);
+        assert_eq!(top_spending.top_merchants[0].total, 516.0);
+        assert_eq!(top_spending.top_merchants[0].average, 129.0);
+    }
+
+    #[tokio::test]
+    async fn test_search_transactions_success() {
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_spending_scanner_repository
+            .expect_search()
+            .withf(|query, _, _, category, limit| {
+                query == "shoes" && category.as_deref() == Some("SHOPPING") && *limit == 10
+            })
+            .returning(|_, _, _, _, _| {
+                Box::pin(async {
+                    Ok(vec![TransactionMatch {
+                        id: 1,
+                        amount: -2500.0,
+                        category: "SHOPPING".to_string(),
+                        description: "Running shoes".to_string(),
+                        date: "2025-03-02".to_string(),
+                        snippet: "Running [shoes]".to_string(),
+                        rank: -1.5,
+                    }])
+                })
+            });
+
+        let spending_scanner_use_case =
+            SpendingScannerUseCase::new(Arc::new(mock_spending_scanner_repository));
+
+        let result = spending_scanner_use_case
+            .search_transactions(SearchTransactionsModel {
+                query: "shoes".to_string(),
+                filter: None,
+                category: Some("shopping".to_string()),
+                limit: None,
+            })
+            .await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].snippet, "Running [shoes]");
+    }
+
+    fn monthly_spending(
+        id: i32,
+        title: &str,
+        frequency: &str,
+        interval: i32,
+        anchor_date: &str,
+    ) -> MonthlySpending {
+        MonthlySpending {
+            id,
+            title: title.to_string(),
+            amount: 100.0,
+            frequency: frequency.to_string(),
+            interval,
+            anchor_date: anchor_date.to_string(),
+            end_date: None,
+            count: None,
+            category: "BILLS".to_string(),
+            posting: "ConfirmFirst".to_string(),
+            post_from: "2023-10-01".to_string(),
+        }
     }
 
     #[tokio::test]
@@ -233,18 +1144,8 @@ mod tests {
             .returning(|| {
                 Box::pin(async {
                     Ok(vec![
-                        MonthlySpending {
-                            id: 1,
-                            title: "Test 1".to_string(),
-                            amount: 100.0,
-                            due_date: "2023-10-01".to_string(),
-                        },
-                        MonthlySpending {
-                            id: 2,
-                            title: "Test 2".to_string(),
-                            amount: 200.0,
-                            due_date: "2023-10-01".to_string(),
-                        },
+                        monthly_spending(1, "Test 1", "Monthly", 1, "2023-10-01"),
+                        monthly_spending(2, "Test 2", "Monthly", 1, "2023-10-01"),
                     ])
                 })
             });
@@ -266,6 +1167,10 @@ mod tests {
 
         mock_spending_scanner_repository
             .expect_add_monthly_spending()
+            .withf(|dto| {
+                dto.frequency == "Monthly" && dto.interval == 1 && dto.anchor_date.ends_with("-31")
+            })
+            .times(1)
             .returning(|_| Box::pin(async { Ok(1) }));
 
         let spending_scanner_use_case =
@@ -275,11 +1180,165 @@ mod tests {
             .add_monthly_spending(AddMonthlySpendingModel {
                 title: "Test".to_string(),
                 amount: 100.0,
-                due_date: "2023-10-01".to_string(),
+                recurrence: None,
+                due_date: Some("31".to_string()),
+                category: None,
+                posting: PostingMode::ConfirmFirst,
+            })
+            .await;
+
+        assert!(result.is_ok());
+
+        let invalid = spending_scanner_use_case
+            .add_monthly_spending(AddMonthlySpendingModel {
+                title: " ".to_string(),
+                amount: -100.0,
+                recurrence: None,
+                due_date: Some("32".to_string()),
+                category: None,
+                posting: PostingMode::ConfirmFirst,
+            })
+            .await;
+
+        assert!(invalid.is_err());
+
+        let errors = invalid.unwrap_err();
+        let fields = errors
+            .downcast_ref::<ValidationErrors>()
+            .unwrap()
+            .errors
+            .iter()
+            .map(|e| e.field.as_str())
+            .collect::<Vec<&str>>();
+
+        assert_eq!(fields, vec!["title", "amount", "due_date"]);
+    }
+
+    #[tokio::test]
+    async fn test_update_monthly_spending_success() {
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_spending_scanner_repository
+            .expect_view_all_monthly_spending()
+            .returning(|| {
+                Box::pin(async {
+                    Ok(vec![monthly_spending(
+                        1,
+                        "Rent",
+                        "Monthly",
+                        1,
+                        "2023-10-05",
+                    )])
+                })
+            });
+
+        // Only the amount changes, the schedule and its cycles stay as they are
+        mock_spending_scanner_repository
+            .expect_update_monthly_spending()
+            .withf(|id, dto, reschedule_from| {
+                *id == 1
+                    && dto.amount == 150.0
+                    && dto.anchor_date == "2023-10-05"
+                    && dto.post_from == "2023-10-01"
+                    && reschedule_from.is_none()
+            })
+            .times(1)
+            .returning(|_, _, _| Box::pin(async { Ok(()) }));
+
+        // "last" moves it to the end of the month
+        mock_spending_scanner_repository
+            .expect_update_monthly_spending()
+            .withf(|id, dto, reschedule_from| {
+                *id == 1
+                    && dto.title == "Rent"
+                    && dto.anchor_date.ends_with("-31")
+                    && reschedule_from.as_deref() == Some(dto.post_from.as_str())
+            })
+            .times(1)
+            .returning(|_, _, _| Box::pin(async { Ok(()) }));
+
+        let spending_scanner_use_case =
+            SpendingScannerUseCase::new(Arc::new(mock_spending_scanner_repository));
+
+        let update = |amount: Option<f32>, due_date: Option<&str>| {
+            spending_scanner_use_case.update_monthly_spending(UpdateMonthlySpendingModel {
+                id: 1,
+                title: None,
+                amount,
+                recurrence: None,
+                due_date: due_date.map(str::to_string),
+                category: None,
+                posting: None,
+            })
+        };
+
+        let result = update(Some(150.0), None).await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().amount, 150.0);
+
+        assert!(update(None, Some("last")).await.is_ok());
+        assert!(update(None, Some("0")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_monthly_spending_occurrences_success() {
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_spending_scanner_repository
+            .expect_view_all_monthly_spending()
+            .returning(|| {
+                Box::pin(async {
+                    let mut allowance = monthly_spending(3, "Allowance", "Weekly", 2, "2024-01-05");
+                    allowance.count = Some(3);
+
+                    let mut tax = monthly_spending(4, "Tax", "Monthly", 3, "2023-01-31");
+                    tax.end_date = Some("2024-04-30".to_string());
+
+                    Ok(vec![
+                        monthly_spending(1, "Rent", "Monthly", 1, "2023-10-31"),
+                        monthly_spending(2, "Insurance", "Yearly", 1, "2020-02-29"),
+                        allowance,
+                        tax,
+                    ])
+                })
+            });
+
+        let spending_scanner_use_case =
+            SpendingScannerUseCase::new(Arc::new(mock_spending_scanner_repository));
+
+        let result = spending_scanner_use_case
+            .monthly_spending_occurrences(SpendingScannerFilter {
+                filter: Range::Custom {
+                    start: "2024-01-01".to_string(),
+                    end: "2024-04-30".to_string(),
+                },
             })
             .await;
 
         assert!(result.is_ok());
+
+        let occurrences = result
+            .unwrap()
+            .iter()
+            .map(|o| format!("{} {}", o.date, o.title))
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            occurrences,
+            vec![
+                "2024-01-05 Allowance",
+                "2024-01-19 Allowance",
+                "2024-01-31 Rent",
+                "2024-01-31 Tax",
+                "2024-02-02 Allowance",
+                "2024-02-29 Rent",
+                "2024-02-29 Insurance",
+                "2024-03-31 Rent",
+                "2024-04-30 Rent",
+                "2024-04-30 Tax",
+            ]
+        );
     }
 
     #[tokio::test]
@@ -0,0 +1,624 @@
+use anyhow::{Result, anyhow};
+use chrono::{Days, Local, Months, NaiveDate};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use crate::{
+    application::{statistics::median, use_cases::spending_scanner::day_after},
+    domain::{
+        entities::my_ledger::MyLedger,
+        repositories::spending_scanner::SpendingScannerRepository,
+        value_objects::{
+            bills::PostingMode,
+            recurrence::{Frequency, RecurrenceModel},
+            spending_scanner::{AddMonthlySpendingModel, SpendingScannerFilter},
+            subscription::{
+                CostCategoryModel, CostPartModel, FixedCostModel, FixedCostSource,
+                FixedVariableCostModel, SubscriptionDetectionModel, SubscriptionFilter,
+                SuspectedSubscriptionModel,
+            },
+        },
+    },
+};
+
+const DEFAULT_LOOKBACK_MONTHS: u32 = 24;
+/// Fewer charges than this is not a pattern yet.
+const MIN_CHARGES: usize = 3;
+/// Share of the gaps and amounts that must fit the cadence, so one skipped or discounted
+/// month does not hide a subscription.
+const REGULAR_SHARE: f32 = 0.75;
+/// How far a charge may be from the typical amount, as a share of it.
+const AMOUNT_TOLERANCE: f32 = 0.2;
+
+struct Cadence {
+    frequency: Frequency,
+    interval: u32,
+    days: f32,
+    tolerance_days: f32,
+    per_year: f32,
+    label: &'static str,
+}
+
+const CADENCES: [Cadence; 6] = [
+    Cadence {
+        frequency: Frequency::Weekly,
+        interval: 1,
+        days: 7.0,
+        tolerance_days: 1.5,
+        per_year: 52.0,
+        label: "weekly",
+    },
+    Cadence {
+        frequency: Frequency::Weekly,
+        interval: 2,
+        days: 14.0,
+        tolerance_days: 2.0,
+        per_year: 26.0,
+        label: "every 2 weeks",
+    },
+    Cadence {
+        frequency: Frequency::Monthly,
+        interval: 1,
+        days: 30.44,
+        tolerance_days: 4.0,
+        per_year: 12.0,
+        label: "monthly",
+    },
+    Cadence {
+        frequency: Frequency::Monthly,
+        interval: 3,
+        days: 91.31,
+        tolerance_days: 8.0,
+        per_year: 4.0,
+        label: "quarterly",
+    },
+    Cadence {
+        frequency: Frequency::Monthly,
+        interval: 6,
+        days: 182.62,
+        tolerance_days: 12.0,
+        per_year: 2.0,
+        label: "every 6 months",
+    },
+    Cadence {
+        frequency: Frequency::Yearly,
+        interval: 1,
+        days: 365.25,
+        tolerance_days: 15.0,
+        per_year: 1.0,
+        label: "yearly",
+    },
+];
+
+#[derive(Clone)]
+pub struct SubscriptionUseCase {
+    spending_scanner_repository: Arc<dyn SpendingScannerRepository + Send + Sync + 'static>,
+}
+
+impl SubscriptionUseCase {
+    pub fn new(
+        spending_scanner_repository: Arc<dyn SpendingScannerRepository + Send + Sync + 'static>,
+    ) -> Self {
+        Self {
+            spending_scanner_repository,
+        }
+    }
+
+    /// Looks for expenses with the same description that repeat at a regular cadence for a
+    /// similar amount. Descriptions are compared without digits and punctuation, so order or
+    /// invoice numbers in them do not split a merchant up.
+    pub async fn detect(
+        &self,
+        subscription_filter: SubscriptionFilter,
+    ) -> Result<SubscriptionDetectionModel> {
+        self.detect_on(subscription_filter, Local::now().date_naive())
+            .await
+    }
+
+    async fn detect_on(
+        &self,
+        subscription_filter: SubscriptionFilter,
+        today: NaiveDate,
+    ) -> Result<SubscriptionDetectionModel> {
+        let lookback_months = subscription_filter
+            .lookback_months
+            .unwrap_or(DEFAULT_LOOKBACK_MONTHS)
+            .max(1);
+        let start = today
+            .checked_sub_months(Months::new(lookback_months))
+            .ok_or_else(|| anyhow!("Lookback of {} months is too long", lookback_months))?;
+
+        let entries = self
+            .spending_scanner_repository
+            .custom(start.to_string(), day_after(today))
+            .await?;
+        let scheduled = self.scheduled_merchants().await?;
+
+        let mut subscriptions = charges_by_merchant(&entries)
+            .iter()
+            .filter_map(|(key, charges)| {
+                let mut subscription = suspected_subscription(charges, today)?;
+
+                subscription.tracked = scheduled.iter().any(|(title, _)| same_merchant(key, title));
+                if subscription.tracked || !subscription.active {
+                    subscription.suggestion = None;
+                }
+
+                Some(subscription)
+            })
+            .collect::<Vec<SuspectedSubscriptionModel>>();
+        subscriptions.sort_by(|a, b| {
+            b.active
+                .cmp(&a.active)
+                .then(b.annual_cost.total_cmp(&a.annual_cost))
+        });
+
+        Ok(SubscriptionDetectionModel {
+            start: start.to_string(),
+            end: today.to_string(),
+            untracked_annual_cost: subscriptions
+                .iter()
+                .filter(|s| s.active && !s.tracked)
+                .map(|s| s.annual_cost)
+                .sum(),
+            subscriptions,
+        })
+    }
+
+    /// Splits the expenses in the range into fixed costs, those matching a scheduled item or a
+    /// subscription detected in the ledger up to the end of the range, and variable costs.
+    pub async fn fixed_vs_variable(
+        &self,
+        spending_scanner_filter: SpendingScannerFilter,
+    ) -> Result<FixedVariableCostModel> {
+        let (start, end) = spending_scanner_filter
+            .filter
+            .bounds(Local::now().date_naive())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Fixed and variable costs need a bounded date range, got {:?}",
+                    spending_scanner_filter.filter
+                )
+            })?;
+
+        // Subscriptions are detected over the usual lookback even for a short range, or from the
+        // start of the range when the lookback would go past the earliest date
+        let history_start = end
+            .checked_sub_months(Months::new(DEFAULT_LOOKBACK_MONTHS))
+            .map_or(start, |lookback_start| start.min(lookback_start));
+        let history = self
+            .spending_scanner_repository
+            .custom(history_start.to_string(), day_after(end))
+            .await?;
+
+        let scheduled = self.scheduled_merchants().await?;
+        let subscriptions = charges_by_merchant(&history)
+            .iter()
+            .filter_map(|(key, charges)| {
+                let subscription = suspected_subscription(charges, end)?;
+                Some((key.to_owned(), subscription.description))
+            })
+            .collect::<Vec<(String, String)>>();
+
+        let mut income = 0.0;
+        let mut fixed = HashMap::new();
+        let mut variable = HashMap::new();
+        let mut fixed_costs = HashMap::new();
+        for entry in history
+            .iter()
+            .filter(|r| r.naive_date().is_some_and(|d| d >= start && d <= end))
+        {
+            if entry.amount >= 0.0 {
+                income += entry.amount;
+                continue;
+            }
+            let spent = -entry.amount;
+
+            let key = merchant_key(&entry.description);
+            let matched = scheduled
+                .iter()
+                .find(|(title, _)| same_merchant(&key, title))
+                .map(|(_, name)| (name.to_owned(), FixedCostSource::Scheduled))
+                .or_else(|| {
+                    subscriptions
+                        .iter()
+                        .find(|(subscription, _)| *subscription == key)
+                        .map(|(_, name)| (name.to_owned(), FixedCostSource::Subscription))
+                });
+
+            match matched {
+                Some(fixed_cost) => {
+                    *fixed.entry(entry.category.to_owned()).or_insert(0.0) += spent;
+                    *fixed_costs.entry(fixed_cost).or_insert(0.0) += spent;
+                }
+                None => *variable.entry(entry.category.to_owned()).or_insert(0.0) += spent,
+            }
+        }
+
+        let expense = fixed.values().sum::<f32>() + variable.values().sum::<f32>();
+
+        let mut fixed_costs = fixed_costs
+            .into_iter()
+            .map(|((name, source), total)| FixedCostModel {
+                name,
+                source,
+                total,
+            })
+            .collect::<Vec<FixedCostModel>>();
+        fixed_costs.sort_by(|a, b| b.total.total_cmp(&a.total).then(a.name.cmp(&b.name)));
+
+        Ok(FixedVariableCostModel {
+            start: start.to_string(),
+            end: end.to_string(),
+            income,
+            expense,
+            fixed: cost_part(fixed, expense, income),
+            variable: cost_part(variable, expense, income),
+            fixed_costs,
+        })
+    }
+
+    /// Normalized title and title of every scheduled item.
+    async fn scheduled_merchants(&self) -> Result<Vec<(String, String)>> {
+        let items = self
+            .spending_scanner_repository
+            .view_all_monthly_spending()
+            .await?;
+
+        Ok(items
+            .iter()
+            .map(|item| (merchant_key(&item.title), item.title.to_owned()))
+            .filter(|(title, _)| !title.is_empty())
+            .collect())
+    }
+}
+
+fn cost_part(categories: HashMap<String, f32>, expense: f32, income: f32) -> CostPartModel {
+    let total = categories.values().sum::<f32>();
+
+    let mut categories = categories
+        .into_iter()
+        .map(|(category, spent)| CostCategoryModel {
+            category,
+            total: spent,
+            share_of_part: percentage(spent, total),
+        })
+        .collect::<Vec<CostCategoryModel>>();
+    categories.sort_by(|a, b| {
+        b.total
+            .total_cmp(&a.total)
+            .then(a.category.cmp(&b.category))
+    });
+
+    CostPartModel {
+        total,
+        share_of_spending: percentage(total, expense),
+        share_of_income: match income > 0.0 {
+            true => Some(total / income * 100.0),
+            false => None,
+        },
+        categories,
+    }
+}
+
+fn percentage(value: f32, total: f32) -> f32 {
+    match total == 0.0 {
+        true => 0.0,
+        false => value / total * 100.0,
+    }
+}
+
+/// Either one's words appear as a whole in the other, so "Netflix" matches "netflix com" but
+/// "gym" does not match "gymnastics".
+fn same_merchant(key: &str, other: &str) -> bool {
+    if key.is_empty() || other.is_empty() {
+        return false;
+    }
+
+    let key = format!(" {} ", key);
+    let other = format!(" {} ", other);
+    key.contains(&other) || other.contains(&key)
+}
+
+/// Lowercased words of a description without digits and punctuation.
+fn merchant_key(description: &str) -> String {
+    description
+        .to_lowercase()
+        .chars()
+        .map(|c| match c.is_alphabetic() {
+            true => c,
+            false => ' ',
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Expenses per merchant in date order, one per day.
+fn charges_by_merchant(entries: &[MyLedger]) -> BTreeMap<String, Vec<(NaiveDate, &MyLedger)>> {
+    let mut merchants = entries
+        .iter()
+        .filter(|r| r.amount < 0.0)
+        .filter_map(|r| Some((merchant_key(&r.description), (r.naive_date()?, r))))
+        .filter(|(key, _)| !key.is_empty())
+        .fold(
+            BTreeMap::new(),
+            |mut acc: BTreeMap<String, HashMap<NaiveDate, &MyLedger>>, (key, (date, r))| {
+                acc.entry(key).or_default().insert(date, r);
+                acc
+            },
+        )
+        .into_iter()
+        .map(|(key, by_date)| {
+            let mut charges = by_date.into_iter().collect::<Vec<(NaiveDate, &MyLedger)>>();
+            charges.sort_by_key(|(date, _)| *date);
+            (key, charges)
+        })
+        .collect::<BTreeMap<String, Vec<(NaiveDate, &MyLedger)>>>();
+
+    merchants.retain(|_, charges| charges.len() >= MIN_CHARGES);
+    merchants
+}
+
+fn suspected_subscription(
+    charges: &[(NaiveDate, &MyLedger)],
+    today: NaiveDate,
+) -> Option<SuspectedSubscriptionModel> {
+    let gaps = charges
+        .windows(2)
+        .map(|w| (w[1].0 - w[0].0).num_days() as f32)
+        .collect::<Vec<f32>>();
+    let median_gap = median(&gaps)?;
+
+    let cadence = CADENCES
+        .iter()
+        .find(|c| (median_gap - c.days).abs() <= c.tolerance_days)?;
+    let regular_gaps = gaps
+        .iter()
+        .filter(|gap| (*gap - cadence.days).abs() <= cadence.tolerance_days)
+        .count();
+    if (regular_gaps as f32) < gaps.len() as f32 * REGULAR_SHARE {
+        return None;
+    }
+
+    let amounts = charges.iter().map(|(_, r)| -r.amount).collect::<Vec<f32>>();
+    let typical = median(&amounts)?;
+    let similar = amounts
+        .iter()
+        .filter(|amount| (*amount - typical).abs() <= typical * AMOUNT_TOLERANCE)
+        .count();
+    if (similar as f32) < amounts.len() as f32 * REGULAR_SHARE {
+        return None;
+    }
+
+    let (last_date, last) = charges.last()?;
+    let recurrence = RecurrenceModel {
+        frequency: cadence.frequency,
+        interval: cadence.interval,
+        anchor_date: last_date.to_string(),
+        end_date: None,
+        count: None,
+    };
+    let next_expected = recurrence.nth(*last_date, 1)?;
+    let active = next_expected + Days::new(cadence.tolerance_days.ceil() as u64) >= today;
+    let last_amount = -last.amount;
+
+    Some(SuspectedSubscriptionModel {
+        description: last.description.to_owned(),
+        category: last.category.to_owned(),
+        cadence: cadence.label.to_string(),
+        frequency: cadence.frequency,
+        interval: cadence.interval,
+        charges: charges.len(),
+        median_interval_days: median_gap,
+        last_charge_date: last_date.to_string(),
+        last_charge_amount: last_amount,
+        next_expected_date: next_expected.to_string(),
+        annual_cost: last_amount * cadence.per_year,
+        active,
+        tracked: false,
+        suggestion: Some(AddMonthlySpendingModel {
+            title: last.description.to_owned(),
+            amount: last_amount,
+            recurrence: Some(recurrence),
+            due_date: None,
+            category: Some(last.category.to_owned()),
+            posting: PostingMode::ConfirmFirst,
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{Months, NaiveDate};
+
+    use crate::{
+        application::use_cases::subscription::SubscriptionUseCase,
+        domain::{
+            entities::{monthly_spending::MonthlySpending, my_ledger::MyLedger},
+            repositories::spending_scanner::MockSpendingScannerRepository,
+            value_objects::{
+                recurrence::Frequency,
+                spending_scanner::{Range, SpendingScannerFilter},
+                subscription::{FixedCostSource, SubscriptionFilter},
+            },
+        },
+    };
+
+    fn ledger(id: i32, amount: f32, description: &str, date: &str) -> MyLedger {
+        MyLedger {
+            id,
+            amount,
+            category: "ENTERTAINMENT".to_string(),
+            description: description.to_string(),
+            date: date.to_string(),
+        }
+    }
+
+    fn subscription_use_case() -> SubscriptionUseCase {
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_spending_scanner_repository
+            .expect_custom()
+            .returning(|_, _| {
+                Box::pin(async {
+                    Ok(vec![
+                        // Invoice numbers differ, the price went up once
+                        ledger(1, -419.0, "NETFLIX.COM #1001", "2025-01-03"),
+                        ledger(2, -419.0, "NETFLIX.COM #1002", "2025-02-03"),
+                        ledger(3, -419.0, "Netflix.com #1003", "2025-03-04"),
+                        ledger(4, -459.0, "NETFLIX.COM #1004", "2025-04-03"),
+                        // Weekly, but cancelled in March
+                        ledger(5, -150.0, "Yoga class", "2025-02-01"),
+                        ledger(6, -150.0, "Yoga class", "2025-02-08"),
+                        ledger(7, -150.0, "Yoga class", "2025-02-15"),
+                        // Already on the monthly spending list
+                        ledger(8, -12000.0, "Rent", "2025-02-01"),
+                        ledger(9, -12000.0, "Rent", "2025-03-01"),
+                        ledger(10, -12000.0, "Rent", "2025-04-01"),
+                        // Regular place, irregular days and amounts
+                        ledger(11, -80.0, "Coffee", "2025-03-01"),
+                        ledger(12, -240.0, "Coffee", "2025-03-03"),
+                        ledger(13, -65.0, "Coffee", "2025-03-20"),
+                        ledger(14, 419.0, "Netflix refund", "2025-04-05"),
+                        ledger(15, -100.0, "Coffee", "2025-04-02"),
+                        ledger(16, -541.0, "Groceries", "2025-04-10"),
+                        ledger(17, 30000.0, "Salary", "2025-04-25"),
+                    ])
+                })
+            });
+
+        mock_spending_scanner_repository
+            .expect_view_all_monthly_spending()
+            .returning(|| {
+                Box::pin(async {
+                    Ok(vec![MonthlySpending {
+                        id: 1,
+                        title: "Rent".to_string(),
+                        amount: 12000.0,
+                        frequency: "Monthly".to_string(),
+                        interval: 1,
+                        anchor_date: "2025-01-01".to_string(),
+                        end_date: None,
+                        count: None,
+                        category: "BILLS".to_string(),
+                        posting: "Auto".to_string(),
+                        post_from: "2025-01-01".to_string(),
+                    }])
+                })
+            });
+
+        SubscriptionUseCase::new(Arc::new(mock_spending_scanner_repository))
+    }
+
+    #[tokio::test]
+    async fn test_detect_success() {
+        let subscription_use_case = subscription_use_case();
+
+        let result = subscription_use_case
+            .detect_on(
+                SubscriptionFilter::default(),
+                NaiveDate::from_ymd_opt(2025, 4, 15).unwrap(),
+            )
+            .await;
+
+        assert!(result.is_ok());
+
+        let result = result.unwrap();
+
+        assert_eq!(result.subscriptions.len(), 3);
+
+        let netflix = &result.subscriptions[1];
+        assert_eq!(netflix.description, "NETFLIX.COM #1004");
+        assert_eq!(netflix.frequency, Frequency::Monthly);
+        assert_eq!(netflix.charges, 4);
+        assert_eq!(netflix.next_expected_date, "2025-05-03");
+        assert_eq!(netflix.annual_cost, 459.0 * 12.0);
+        assert!(netflix.active && !netflix.tracked);
+
+        let suggestion = netflix.suggestion.as_ref().unwrap();
+        assert_eq!(suggestion.amount, 459.0);
+        assert_eq!(
+            suggestion.recurrence.as_ref().unwrap().anchor_date,
+            "2025-04-03"
+        );
+
+        let rent = &result.subscriptions[0];
+        assert!(rent.tracked);
+        assert!(rent.suggestion.is_none());
+
+        let yoga = &result.subscriptions[2];
+        assert_eq!(yoga.cadence, "weekly");
+        assert!(!yoga.active);
+        assert!(yoga.suggestion.is_none());
+
+        assert_eq!(result.untracked_annual_cost, 459.0 * 12.0);
+    }
+
+    #[tokio::test]
+    async fn test_detect_rejects_a_lookback_out_of_range() {
+        let subscription_use_case = subscription_use_case();
+
+        let result = subscription_use_case
+            .detect_on(
+                SubscriptionFilter {
+                    lookback_months: Some(4_000_000),
+                },
+                NaiveDate::from_ymd_opt(2025, 4, 15).unwrap(),
+            )
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fixed_vs_variable_success() {
+        let subscription_use_case = subscription_use_case();
+
+        let result = subscription_use_case
+            .fixed_vs_variable(SpendingScannerFilter {
+                filter: Range::Custom {
+                    start: "2025-04-01".to_string(),
+                    end: "2025-04-30".to_string(),
+                },
+            })
+            .await;
+
+        assert!(result.is_ok());
+
+        let result = result.unwrap();
+
+        assert_eq!(result.income, 30419.0);
+        assert_eq!(result.expense, 13100.0);
+        assert_eq!(result.fixed.total, 12459.0);
+        assert_eq!(result.variable.total, 641.0);
+        assert_eq!(result.fixed_costs.len(), 2);
+        assert_eq!(result.fixed_costs[0].name, "Rent");
+        assert_eq!(result.fixed_costs[0].source, FixedCostSource::Scheduled);
+        assert_eq!(result.fixed_costs[1].name, "NETFLIX.COM #1004");
+        assert_eq!(result.fixed_costs[1].source, FixedCostSource::Subscription);
+        assert_eq!(result.variable.categories[0].category, "ENTERTAINMENT");
+        assert!((result.fixed.share_of_income.unwrap() - 12459.0 / 30419.0 * 100.0).abs() < 0.01);
+    }
+
+    #[tokio::test]
+    async fn test_fixed_vs_variable_on_the_earliest_dates() {
+        let subscription_use_case = subscription_use_case();
+
+        let result = subscription_use_case
+            .fixed_vs_variable(SpendingScannerFilter {
+                filter: Range::Custom {
+                    start: NaiveDate::MIN.to_string(),
+                    end: (NaiveDate::MIN + Months::new(1)).to_string(),
+                },
+            })
+            .await;
+
+        assert!(result.is_ok());
+    }
+}
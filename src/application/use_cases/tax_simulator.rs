@@ -1,27 +1,48 @@
 use anyhow::Result;
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use crate::domain::{
+    entities::my_ledger::IncomeTypeTotal,
     repositories::tax_simulator::TaxSimulatorRepository,
     value_objects::tax_simulator::{
-        AddTaxDeductionsListModel, RemoveTaxDeductionsListModel, TaxDeductionsListModel,
+        AddTaxDeductionsListModel, ExpenseDeductionModel, IncomeByYearFilter, IncomeModel,
+        IncomeType, RemoveTaxDeductionsListModel, TaxBracketModel, TaxDeductionsListModel,
         TaxSimulateRequestModel, TaxSimulateResult,
     },
 };
 
 /// Reference: https://www.rd.go.th/59670.html
-/// (Lower, Upper, TaxRate, Constraint)
-const TAX_RANGE: &[(f32, f32, f32, f32)] = &[
-    (0.0, 150_000.0, 0.0, 0.0),
-    (150_000.0, 300_000.0, 0.05, 0.0),
-    (300_000.0, 500_000.0, 0.1, 27_500.0),
-    (500_000.0, 750_000.0, 0.15, 27_500.0),
-    (750_000.0, 1_000_000.0, 0.2, 65_000.0),
-    (1_000_000.0, 2_000_000.0, 0.25, 115_000.0),
-    (2_000_000.0, 5_000_000.0, 0.3, 365_000.0),
-    (5_000_000.0, f32::MAX, 0.35, 1_265_000.0),
+/// (Lower, Upper, TaxRate), each rate only applies to the part of the income inside its range
+const TAX_RANGE: &[(f32, f32, f32)] = &[
+    (0.0, 150_000.0, 0.0),
+    (150_000.0, 300_000.0, 0.05),
+    (300_000.0, 500_000.0, 0.1),
+    (500_000.0, 750_000.0, 0.15),
+    (750_000.0, 1_000_000.0, 0.2),
+    (1_000_000.0, 2_000_000.0, 0.25),
+    (2_000_000.0, 5_000_000.0, 0.3),
+    (5_000_000.0, f32::MAX, 0.35),
 ];
 
+/// Statutory expense deduction of an income type: (Rule, Rate, Cap).
+/// Types under the same rule share its cap, 40(1) and 40(2) together get 50% up to 100,000.
+fn expense_rule(income_type: IncomeType) -> (&'static str, f32, Option<f32>) {
+    match income_type {
+        IncomeType::Salary | IncomeType::Service => ("40(1)-(2)", 0.5, Some(100_000.0)),
+        IncomeType::Royalty => ("40(3)", 0.5, Some(100_000.0)),
+        IncomeType::Investment => ("40(4)", 0.0, None),
+        IncomeType::RentBuilding => ("40(5) buildings", 0.3, None),
+        IncomeType::RentAgriculturalLand => ("40(5) agricultural land", 0.2, None),
+        IncomeType::RentLand => ("40(5) other land", 0.15, None),
+        IncomeType::RentVehicle => ("40(5) vehicles", 0.3, None),
+        IncomeType::RentOther => ("40(5) other property", 0.1, None),
+        IncomeType::Medical => ("40(6) medical", 0.6, None),
+        IncomeType::Professional => ("40(6) professional", 0.3, None),
+        IncomeType::Contracting => ("40(7)", 0.6, None),
+        IncomeType::Business => ("40(8)", 0.6, None),
+    }
+}
+
 #[derive(Clone)]
 pub struct TaxSimulatorUseCase {
     tax_simulator_repository: Arc<dyn TaxSimulatorRepository + Send + Sync + 'static>,
@@ -70,45 +91,117 @@ impl TaxSimulatorUseCase {
         Ok(())
     }
 
+    pub async fn view_all_income_by_year(
+        &self,
+        income_by_year_filter: IncomeByYearFilter,
+    ) -> Result<Vec<IncomeModel>> {
+        let entities = self
+            .tax_simulator_repository
+            .view_all_income_by_year(income_by_year_filter.year)
+            .await?;
+
+        Ok(entities.iter().map(|e| e.to_model()).collect())
+    }
+
     pub async fn simulate(
         &self,
         tax_simulate_request_model: TaxSimulateRequestModel,
     ) -> Result<TaxSimulateResult> {
-        let incomes = self
-            .tax_simulator_repository
-            .view_all_income_by_year(tax_simulate_request_model.year)
-            .await?;
+        let year = tax_simulate_request_model.year;
 
-        let tax_deductions_list = self
-            .tax_simulator_repository
-            .view_all_tax_deductions_list()
-            .await?;
+        // Gross assessable income
+        let incomes = self.tax_simulator_repository.income_by_type(year).await?;
+        let gross_income = incomes.iter().filter_map(|i| i.total).sum::<f32>();
 
-        let mut tax = TaxSimulateResult { must_pay: 0.0 };
+        // Expense deduction
+        let expense_deductions = expense_deductions(&incomes);
+        let expense_deduction = expense_deductions.iter().map(|e| e.deduction).sum::<f32>();
+        let income_after_expenses = gross_income - expense_deduction;
 
-        let total_income = incomes.iter().fold(0.0, |acc, income| acc + income.amount);
+        // Allowances and deductions
+        let allowances = self.view_all_tax_deductions_list().await?;
+        let total_allowances = allowances
+            .iter()
+            .map(|a| a.amount.max(0.0))
+            .sum::<f32>()
+            .min(income_after_expenses);
+
+        // Net taxable income
+        let net_taxable_income = income_after_expenses - total_allowances;
+
+        // Progressive brackets
+        let brackets = progressive_brackets(net_taxable_income);
+        let must_pay = brackets.iter().map(|b| b.tax).sum::<f32>();
+
+        Ok(TaxSimulateResult {
+            year,
+            gross_income,
+            expense_deductions,
+            expense_deduction,
+            income_after_expenses,
+            allowances,
+            total_allowances,
+            net_taxable_income,
+            brackets,
+            must_pay,
+            effective_rate: match gross_income > 0.0 {
+                true => must_pay / gross_income * 100.0,
+                false => 0.0,
+            },
+        })
+    }
+}
 
-        // Calculate original tax
-        for r in TAX_RANGE.iter() {
-            if r.0 > total_income || total_income <= r.1 {
-                tax.must_pay = ((total_income - r.0) * r.2) + r.3;
-                break;
-            }
+/// Income summed per expense rule, each rule deducts its rate of that income up to its cap.
+fn expense_deductions(incomes: &[IncomeTypeTotal]) -> Vec<ExpenseDeductionModel> {
+    let mut rules: BTreeMap<&str, ExpenseDeductionModel> = BTreeMap::new();
+
+    for income in incomes {
+        let income_type = income.income_type();
+        let (rule, rate, cap) = expense_rule(income_type);
+
+        let entry = rules.entry(rule).or_insert_with(|| ExpenseDeductionModel {
+            rule: rule.to_string(),
+            income_types: Vec::new(),
+            income: 0.0,
+            rate,
+            cap,
+            deduction: 0.0,
+        });
+
+        if !entry.income_types.contains(&income_type) {
+            entry.income_types.push(income_type);
         }
+        entry.income += income.total.unwrap_or(0.0);
+    }
 
-        // Deduction calculate
-        for td in tax_deductions_list.iter() {
-            tax.must_pay -= td.amount;
-        }
+    rules
+        .into_values()
+        .map(|mut e| {
+            let deduction = e.income * e.rate;
+            e.deduction = e.cap.map_or(deduction, |cap| deduction.min(cap));
+            e
+        })
+        .collect()
+}
 
-        match tax.must_pay <= 0.0 {
-            true => {
-                tax.must_pay = 0.0;
-                Ok(tax)
+/// Every bracket the income reaches, with the part of the income taxed at its rate.
+fn progressive_brackets(net_taxable_income: f32) -> Vec<TaxBracketModel> {
+    TAX_RANGE
+        .iter()
+        .take_while(|(lower, _, _)| *lower < net_taxable_income)
+        .map(|(lower, upper, rate)| {
+            let taxable = net_taxable_income.min(*upper) - lower;
+
+            TaxBracketModel {
+                lower: *lower,
+                upper: (*upper < f32::MAX).then_some(*upper),
+                rate: *rate,
+                taxable,
+                tax: taxable * rate,
             }
-            _ => Ok(tax),
-        }
-    }
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -118,10 +211,11 @@ mod tests {
     use crate::{
         application::use_cases::tax_simulator::TaxSimulatorUseCase,
         domain::{
-            entities::{my_ledger::MyLedger, tax_deductions_list::TaxDeductionsList},
+            entities::{my_ledger::IncomeTypeTotal, tax_deductions_list::TaxDeductionsList},
             repositories::tax_simulator::MockTaxSimulatorRepository,
             value_objects::tax_simulator::{
-                AddTaxDeductionsListModel, RemoveTaxDeductionsListModel,
+                AddTaxDeductionsListModel, IncomeType, RemoveTaxDeductionsListModel,
+                TaxSimulateRequestModel,
             },
         },
     };
@@ -199,46 +293,20 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    fn income(total: f32, income_type: Option<IncomeType>) -> IncomeTypeTotal {
+        IncomeTypeTotal {
+            income_type: income_type.map(|t| t.as_str().to_string()),
+            total: Some(total),
+        }
+    }
+
     #[tokio::test]
     async fn test_simulate_success() {
         let mut mock_tax_simulator_repository = MockTaxSimulatorRepository::new();
 
         mock_tax_simulator_repository
-            .expect_view_all_income_by_year()
-            .returning(|_| {
-                Box::pin(async {
-                    Ok(vec![
-                        MyLedger {
-                            id: 1,
-                            amount: 250_000.0,
-                            category: "INCOME".to_string(),
-                            date: "2025-01-01".to_string(),
-                            description: "Monthly Salary".to_string(),
-                        },
-                        MyLedger {
-                            id: 2,
-                            amount: 250_000.0,
-                            category: "SALARY".to_string(),
-                            date: "2025-02-01".to_string(),
-                            description: "Monthly Salary".to_string(),
-                        },
-                        MyLedger {
-                            id: 3,
-                            amount: 250_000.0,
-                            category: "INCOME".to_string(),
-                            date: "2025-03-01".to_string(),
-                            description: "Monthly Salary".to_string(),
-                        },
-                        MyLedger {
-                            id: 4,
-                            amount: 250_000.0,
-                            category: "INCOME".to_string(),
-                            date: "2025-04-01".to_string(),
-                            description: "Monthly Salary".to_string(),
-                        },
-                    ])
-                })
-            });
+            .expect_income_by_type()
+            .returning(|_| Box::pin(async { Ok(vec![income(1_000_000.0, None)]) }));
 
         mock_tax_simulator_repository
             .expect_view_all_tax_deductions_list()
@@ -259,19 +327,74 @@ mod tests {
                 })
             });
 
-        let expected = 46000.0;
-
         let tax_simulator_use_case =
             TaxSimulatorUseCase::new(Arc::new(mock_tax_simulator_repository));
 
-        let tax_simulate_request_model =
-            crate::domain::value_objects::tax_simulator::TaxSimulateRequestModel { year: 2025 };
-
         let result = tax_simulator_use_case
-            .simulate(tax_simulate_request_model)
+            .simulate(TaxSimulateRequestModel { year: 2025 })
             .await;
 
         assert!(result.is_ok());
-        assert_eq!(result.unwrap().must_pay, expected);
+
+        let result = result.unwrap();
+
+        // 1,000,000 - 100,000 expenses - 69,000 allowances
+        assert_eq!(result.expense_deduction, 100_000.0);
+        assert_eq!(result.total_allowances, 69_000.0);
+        assert_eq!(result.net_taxable_income, 831_000.0);
+        // 7,500 + 20,000 + 37,500 + 81,000 * 20%
+        assert_eq!(result.brackets.len(), 5);
+        assert_eq!(result.brackets[4].taxable, 81_000.0);
+        assert_eq!(result.must_pay, 81_200.0);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_deducts_expenses_per_income_type() {
+        let mut mock_tax_simulator_repository = MockTaxSimulatorRepository::new();
+
+        mock_tax_simulator_repository
+            .expect_income_by_type()
+            .returning(|_| {
+                Box::pin(async {
+                    Ok(vec![
+                        income(150_000.0, Some(IncomeType::Salary)),
+                        income(100_000.0, Some(IncomeType::Service)),
+                        income(200_000.0, Some(IncomeType::RentBuilding)),
+                        income(50_000.0, Some(IncomeType::RentLand)),
+                        income(30_000.0, Some(IncomeType::Investment)),
+                        income(100_000.0, Some(IncomeType::Business)),
+                    ])
+                })
+            });
+
+        mock_tax_simulator_repository
+            .expect_view_all_tax_deductions_list()
+            .returning(|| Box::pin(async { Ok(vec![]) }));
+
+        let tax_simulator_use_case =
+            TaxSimulatorUseCase::new(Arc::new(mock_tax_simulator_repository));
+
+        let result = tax_simulator_use_case
+            .simulate(TaxSimulateRequestModel { year: 2025 })
+            .await
+            .unwrap();
+
+        let deduction = |rule: &str| {
+            result
+                .expense_deductions
+                .iter()
+                .find(|e| e.rule == rule)
+                .map(|e| e.deduction)
+                .unwrap()
+        };
+
+        assert_eq!(result.gross_income, 630_000.0);
+        // 40(1) and 40(2) share the 100,000 cap
+        assert_eq!(deduction("40(1)-(2)"), 100_000.0);
+        assert_eq!(deduction("40(4)"), 0.0);
+        assert!((deduction("40(5) buildings") - 60_000.0).abs() < 0.01);
+        assert!((deduction("40(5) other land") - 7_500.0).abs() < 0.01);
+        assert!((deduction("40(8)") - 60_000.0).abs() < 0.01);
+        assert!((result.net_taxable_income - 402_500.0).abs() < 0.01);
     }
 }
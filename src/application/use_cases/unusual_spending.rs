@@ -0,0 +1,515 @@
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Days, Local, Months, NaiveDate};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+};
+
+use crate::{
+    application::{
+        statistics::{mean, median, robust_z_score},
+        use_cases::spending_scanner::day_after,
+    },
+    domain::{
+        entities::my_ledger::{MyLedger, RecordMyLedgerDto},
+        repositories::spending_scanner::SpendingScannerRepository,
+        value_objects::{
+            spending_scanner::Grouping,
+            unusual_spending::{
+                Baseline, UnusualCategoryModel, UnusualSpendingFilter, UnusualSpendingModel,
+                UnusualTransactionModel,
+            },
+        },
+    },
+};
+
+const DEFAULT_BASELINE_MONTHS: u32 = 6;
+const DEFAULT_THRESHOLD: f32 = 3.5;
+/// Fewer past expenses than this in a category is not enough to call anything unusual.
+const MIN_HISTORY: usize = 5;
+
+#[derive(Clone)]
+pub struct UnusualSpendingUseCase {
+    spending_scanner_repository: Arc<dyn SpendingScannerRepository + Send + Sync + 'static>,
+}
+
+impl UnusualSpendingUseCase {
+    pub fn new(
+        spending_scanner_repository: Arc<dyn SpendingScannerRepository + Send + Sync + 'static>,
+    ) -> Self {
+        Self {
+            spending_scanner_repository,
+        }
+    }
+
+    pub async fn detect(
+        &self,
+        unusual_spending_filter: UnusualSpendingFilter,
+    ) -> Result<UnusualSpendingModel> {
+        let today = Local::now().date_naive();
+        let (start, end) = unusual_spending_filter
+            .filter
+            .bounds(today)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unusual spending needs a bounded date range, got {:?}",
+                    unusual_spending_filter.filter
+                )
+            })?;
+
+        let baseline_months = unusual_spending_filter
+            .baseline_months
+            .unwrap_or(DEFAULT_BASELINE_MONTHS)
+            .max(1);
+        let threshold = unusual_spending_filter
+            .threshold
+            .unwrap_or(DEFAULT_THRESHOLD);
+
+        let (baseline_start, _) = Grouping::Month.bucket_bounds(
+            start
+                .checked_sub_months(Months::new(baseline_months))
+                .ok_or_else(|| anyhow!("Baseline of {} months is too long", baseline_months))?,
+        );
+        let baseline_end = start - Days::new(1);
+
+        let history = self
+            .spending_scanner_repository
+            .custom(baseline_start.to_string(), start.to_string())
+            .await?;
+        let current = self
+            .spending_scanner_repository
+            .custom(start.to_string(), day_after(end))
+            .await?;
+
+        let expenses_history = expenses_by_category(&history);
+
+        let mut transactions = current
+            .iter()
+            .filter_map(|r| unusual_transaction(r, &expenses_history, threshold))
+            .collect::<Vec<UnusualTransactionModel>>();
+        transactions.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        // Only the days up to today can have spending, a month in progress is not a full month
+        let period_days = ((end.min(today) - start).num_days() + 1).max(1);
+
+        let mut categories = unusual_categories(&history, &current, threshold, start, period_days);
+        categories.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        Ok(UnusualSpendingModel {
+            baseline_start: baseline_start.to_string(),
+            baseline_end: baseline_end.to_string(),
+            transactions,
+            categories,
+        })
+    }
+
+    /// Checks a freshly recorded entry against the last months of its category.
+    pub async fn check_recorded(
+        &self,
+        id: i32,
+        record_my_ledger_dto: RecordMyLedgerDto,
+    ) -> Result<Option<UnusualTransactionModel>> {
+        let entry = MyLedger {
+            id,
+            amount: record_my_ledger_dto.amount,
+            category: record_my_ledger_dto.category,
+            description: record_my_ledger_dto.description,
+            date: record_my_ledger_dto.date,
+        };
+
+        let Some(date) = entry.naive_date() else {
+            return Ok(None);
+        };
+
+        if entry.amount >= 0.0 {
+            return Ok(None);
+        }
+
+        let history = self
+            .spending_scanner_repository
+            .custom(
+                (date - Months::new(DEFAULT_BASELINE_MONTHS)).to_string(),
+                date.to_string(),
+            )
+            .await?;
+
+        Ok(unusual_transaction(
+            &entry,
+            &expenses_by_category(&history),
+            DEFAULT_THRESHOLD,
+        ))
+    }
+}
+
+/// Expenses are stored as negative amounts, this returns how much was spent per category.
+fn expenses_by_category(entries: &[MyLedger]) -> HashMap<String, Vec<f32>> {
+    entries
+        .iter()
+        .filter(|r| r.amount < 0.0)
+        .fold(HashMap::new(), |mut acc, r| {
+            acc.entry(r.category.clone())
+                .or_insert_with(Vec::new)
+                .push(-r.amount);
+            acc
+        })
+}
+
+fn unusual_transaction(
+    entry: &MyLedger,
+    expenses_history: &HashMap<String, Vec<f32>>,
+    threshold: f32,
+) -> Option<UnusualTransactionModel> {
+    if entry.amount >= 0.0 {
+        return None;
+    }
+
+    let history = expenses_history.get(&entry.category)?;
+    if history.len() < MIN_HISTORY {
+        return None;
+    }
+
+    let spent = -entry.amount;
+    let score = robust_z_score(spent, history)?;
+
+    match score >= threshold {
+        true => Some(UnusualTransactionModel {
+            id: entry.id,
+            date: entry.date.to_owned(),
+            category: entry.category.to_owned(),
+            description: entry.description.to_owned(),
+            spent,
+            baseline_median: median(history)?,
+            score,
+        }),
+        _ => None,
+    }
+}
+
+/// Compares each category total with its totals in the history. A single day is compared with
+/// the same weekday, longer ranges with monthly totals scaled to the range's length. Only days
+/// or months with any ledger activity count, so a ledger that was started recently does not
+/// look like a spike.
+fn unusual_categories(
+    history: &[MyLedger],
+    current: &[MyLedger],
+    threshold: f32,
+    start: NaiveDate,
+    period_days: i64,
+) -> Vec<UnusualCategoryModel> {
+    let baseline = match period_days {
+        1 => Baseline::Weekday,
+        _ => Baseline::Month,
+    };
+
+    let period_of = |date: NaiveDate| match baseline {
+        Baseline::Weekday => (date.weekday() == start.weekday()).then_some(date),
+        Baseline::Month => Some(month_start(date)),
+    };
+    let scale = |period: NaiveDate| match baseline {
+        Baseline::Weekday => 1.0,
+        Baseline::Month => {
+            let (first, last) = Grouping::Month.bucket_bounds(period);
+            period_days as f32 / ((last - first).num_days() + 1) as f32
+        }
+    };
+
+    let active_periods = history
+        .iter()
+        .filter_map(|r| period_of(r.naive_date()?))
+        .collect::<BTreeSet<NaiveDate>>();
+
+    let history_by_period = history
+        .iter()
+        .filter(|r| r.amount < 0.0)
+        .filter_map(|r| Some((r.category.clone(), period_of(r.naive_date()?)?, -r.amount)))
+        .fold(
+            HashMap::new(),
+            |mut acc: HashMap<String, HashMap<NaiveDate, f32>>, (category, period, spent)| {
+                *acc.entry(category)
+                    .or_default()
+                    .entry(period)
+                    .or_insert(0.0) += spent;
+                acc
+            },
+        );
+
+    let current_totals = expenses_by_category(current);
+
+    current_totals
+        .iter()
+        .filter_map(|(category, spent)| {
+            let by_period = history_by_period.get(category);
+            let baseline_totals = active_periods
+                .iter()
+                .map(|period| {
+                    by_period
+                        .and_then(|p| p.get(period))
+                        .copied()
+                        .unwrap_or(0.0)
+                        * scale(*period)
+                })
+                .collect::<Vec<f32>>();
+
+            if baseline_totals.len() < 2 {
+                return None;
+            }
+
+            let spent = spent.iter().sum::<f32>();
+            let score = robust_z_score(spent, &baseline_totals)?;
+
+            match score >= threshold {
+                true => Some(UnusualCategoryModel {
+                    category: category.to_owned(),
+                    spent,
+                    baseline,
+                    period_days,
+                    baseline_mean: mean(&baseline_totals)?,
+                    baseline_median: median(&baseline_totals)?,
+                    score,
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn month_start(date: NaiveDate) -> NaiveDate {
+    Grouping::Month.bucket_bounds(date).0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        application::use_cases::unusual_spending::UnusualSpendingUseCase,
+        domain::{
+            entities::my_ledger::{MyLedger, RecordMyLedgerDto},
+            repositories::spending_scanner::MockSpendingScannerRepository,
+            value_objects::{
+                spending_scanner::Range,
+                unusual_spending::{Baseline, UnusualSpendingFilter},
+            },
+        },
+    };
+
+    fn food(id: i32, amount: f32, date: &str) -> MyLedger {
+        MyLedger {
+            id,
+            amount,
+            category: "FOOD".to_string(),
+            description: "Lunch".to_string(),
+            date: date.to_string(),
+        }
+    }
+
+    fn food_history() -> Vec<MyLedger> {
+        vec![
+            food(1, -120.0, "2025-01-05"),
+            food(2, -100.0, "2025-01-20"),
+            food(3, -130.0, "2025-02-03"),
+            food(4, -110.0, "2025-02-18"),
+            food(5, -125.0, "2025-03-07"),
+            food(6, -105.0, "2025-03-22"),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_detect_success() {
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_spending_scanner_repository
+            .expect_custom()
+            .returning(|start, _| {
+                Box::pin(async move {
+                    match start.as_str() {
+                        "2025-04-01" => Ok(vec![
+                            food(7, -115.0, "2025-04-02"),
+                            food(8, -2500.0, "2025-04-03"),
+                        ]),
+                        _ => Ok(food_history()),
+                    }
+                })
+            });
+
+        let unusual_spending_use_case =
+            UnusualSpendingUseCase::new(Arc::new(mock_spending_scanner_repository));
+
+        let result = unusual_spending_use_case
+            .detect(UnusualSpendingFilter {
+                filter: Range::Custom {
+                    start: "2025-04-01".to_string(),
+                    end: "2025-04-30".to_string(),
+                },
+                baseline_months: Some(3),
+                threshold: None,
+            })
+            .await;
+
+        assert!(result.is_ok());
+
+        let unusual_spending = result.unwrap();
+
+        assert_eq!(unusual_spending.baseline_start, "2025-01-01");
+        assert_eq!(unusual_spending.baseline_end, "2025-03-31");
+        assert_eq!(unusual_spending.transactions.len(), 1);
+        assert_eq!(unusual_spending.transactions[0].id, 8);
+        assert_eq!(unusual_spending.categories.len(), 1);
+        assert_eq!(unusual_spending.categories[0].spent, 2615.0);
+        assert_eq!(unusual_spending.categories[0].baseline, Baseline::Month);
+        assert_eq!(unusual_spending.categories[0].period_days, 30);
+    }
+
+    fn detect_filter(start: &str, end: &str) -> UnusualSpendingFilter {
+        UnusualSpendingFilter {
+            filter: Range::Custom {
+                start: start.to_string(),
+                end: end.to_string(),
+            },
+            baseline_months: Some(3),
+            threshold: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_detect_rejects_a_baseline_out_of_range() {
+        let unusual_spending_use_case =
+            UnusualSpendingUseCase::new(Arc::new(MockSpendingScannerRepository::new()));
+
+        let result = unusual_spending_use_case
+            .detect(UnusualSpendingFilter {
+                baseline_months: Some(4_000_000),
+                ..detect_filter("2025-04-01", "2025-04-30")
+            })
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_detect_scales_baseline_to_range() {
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_spending_scanner_repository
+            .expect_custom()
+            .returning(|start, _| {
+                Box::pin(async move {
+                    match start.as_str() {
+                        "2025-04-01" => Ok(vec![
+                            food(7, -100.0, "2025-04-02"),
+                            food(8, -100.0, "2025-04-09"),
+                        ]),
+                        "2025-01-01" => Ok(food_history()),
+                        _ => Ok(vec![]),
+                    }
+                })
+            });
+
+        let unusual_spending_use_case =
+            UnusualSpendingUseCase::new(Arc::new(mock_spending_scanner_repository));
+
+        // 200 in ten days is below any full month, but close to triple the usual pace
+        let partial_month = unusual_spending_use_case
+            .detect(detect_filter("2025-04-01", "2025-04-10"))
+            .await
+            .unwrap();
+
+        assert_eq!(partial_month.categories.len(), 1);
+        assert_eq!(partial_month.categories[0].period_days, 10);
+        assert!(partial_month.categories[0].baseline_median < 100.0);
+
+        // The same spending over a whole quarter is nothing special
+        let quarter = unusual_spending_use_case
+            .detect(detect_filter("2025-04-01", "2025-06-30"))
+            .await
+            .unwrap();
+
+        assert!(quarter.categories.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_detect_single_day_uses_weekday_baseline() {
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_spending_scanner_repository
+            .expect_custom()
+            .returning(|start, _| {
+                Box::pin(async move {
+                    match start.as_str() {
+                        // Saturday
+                        "2025-04-05" => Ok(vec![food(20, -600.0, "2025-04-05")]),
+                        // Monday
+                        "2025-04-07" => Ok(vec![food(21, -600.0, "2025-04-07")]),
+                        _ => Ok(vec![
+                            food(1, -100.0, "2025-03-03"),
+                            food(2, -110.0, "2025-03-10"),
+                            food(3, -90.0, "2025-03-17"),
+                            food(4, -105.0, "2025-03-24"),
+                            food(5, -550.0, "2025-03-08"),
+                            food(6, -650.0, "2025-03-15"),
+                            food(7, -600.0, "2025-03-22"),
+                            food(8, -580.0, "2025-03-29"),
+                        ]),
+                    }
+                })
+            });
+
+        let unusual_spending_use_case =
+            UnusualSpendingUseCase::new(Arc::new(mock_spending_scanner_repository));
+
+        let saturday = unusual_spending_use_case
+            .detect(detect_filter("2025-04-05", "2025-04-05"))
+            .await
+            .unwrap();
+        let monday = unusual_spending_use_case
+            .detect(detect_filter("2025-04-07", "2025-04-07"))
+            .await
+            .unwrap();
+
+        assert!(saturday.categories.is_empty());
+        assert_eq!(monday.categories.len(), 1);
+        assert_eq!(monday.categories[0].baseline, Baseline::Weekday);
+        assert_eq!(monday.categories[0].baseline_median, 102.5);
+    }
+
+    #[tokio::test]
+    async fn test_check_recorded_success() {
+        let mut mock_spending_scanner_repository = MockSpendingScannerRepository::new();
+
+        mock_spending_scanner_repository
+            .expect_custom()
+            .returning(|_, _| Box::pin(async { Ok(food_history()) }));
+
+        let unusual_spending_use_case =
+            UnusualSpendingUseCase::new(Arc::new(mock_spending_scanner_repository));
+
+        let usual = unusual_spending_use_case
+            .check_recorded(
+                7,
+                RecordMyLedgerDto {
+                    amount: -118.0,
+                    category: "FOOD".to_string(),
+                    description: "Lunch".to_string(),
+                    date: "2025-04-02".to_string(),
+                    income_type: None,
+                },
+            )
+            .await;
+
+        let unusual = unusual_spending_use_case
+            .check_recorded(
+                8,
+                RecordMyLedgerDto {
+                    amount: -2500.0,
+                    category: "FOOD".to_string(),
+                    description: "Omakase".to_string(),
+                    date: "2025-04-03".to_string(),
+                    income_type: None,
+                },
+            )
+            .await;
+
+        assert!(usual.unwrap().is_none());
+        assert_eq!(unusual.unwrap().unwrap().id, 8);
+    }
+}
@@ -1,12 +1,16 @@
-use std::env;
+use std::{env, path::PathBuf};
 
 use anyhow::Result;
 
 #[derive(Debug, Clone)]
 pub struct DotEnvyConfig {
     pub database_url: String,
+    /// Exported files can only be written inside this directory.
+    pub export_dir: PathBuf,
 }
 
+const DEFAULT_EXPORT_DIR: &str = "exports";
+
 pub fn load() -> Result<DotEnvyConfig> {
     dotenvy::dotenv().ok();
 
@@ -17,5 +21,10 @@ pub fn load() -> Result<DotEnvyConfig> {
             .expect("DATABASE_URL not found in environment variables or command line arguments"),
     };
 
-    Ok(DotEnvyConfig { database_url })
+    let export_dir = dotenvy::var("EXPORT_DIR").unwrap_or_else(|_| DEFAULT_EXPORT_DIR.to_string());
+
+    Ok(DotEnvyConfig {
+        database_url,
+        export_dir: PathBuf::from(export_dir),
+    })
 }
@@ -0,0 +1,40 @@
+use diesel::prelude::*;
+
+use crate::{
+    domain::value_objects::bills::BillStatus, infrastructure::database::schema::bill_occurrences,
+};
+
+/// One cycle of a scheduled payment. `ledger_id` links the transaction that paid it, either
+/// posted by the bill job or an existing one linked when marking it paid.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = bill_occurrences)]
+pub struct BillOccurrence {
+    pub id: i32,
+    pub monthly_spending_id: i32,
+    pub due_date: String,
+    pub ledger_id: Option<i32>,
+    pub status: String,
+    pub paid_date: Option<String>,
+}
+
+impl BillOccurrence {
+    /// Unknown statuses are read as pending, so the cycle shows up as unpaid.
+    pub fn status(&self) -> BillStatus {
+        self.status.parse().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Queryable, Insertable)]
+#[diesel(table_name = bill_occurrences)]
+pub struct AddBillOccurrenceDto {
+    pub monthly_spending_id: i32,
+    pub due_date: String,
+}
+
+#[derive(Debug, Clone, AsChangeset)]
+#[diesel(table_name = bill_occurrences, treat_none_as_null = true)]
+pub struct SettleBillOccurrenceDto {
+    pub status: String,
+    pub paid_date: Option<String>,
+    pub ledger_id: Option<i32>,
+}
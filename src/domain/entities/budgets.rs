@@ -0,0 +1,82 @@
+use diesel::prelude::*;
+
+use crate::{
+    domain::value_objects::budget::{BudgetModel, RolloverPolicy},
+    infrastructure::database::schema::budgets,
+};
+
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = budgets)]
+pub struct Budget {
+    pub id: i32,
+    pub category: String,
+    pub monthly_limit: f32,
+    pub effective_from: Option<String>,
+    pub effective_to: Option<String>,
+    pub rollover: String,
+    pub rollover_cap: Option<f32>,
+}
+
+impl Budget {
+    pub fn to_model(&self) -> BudgetModel {
+        BudgetModel {
+            id: self.id,
+            category: self.category.to_owned(),
+            monthly_limit: self.monthly_limit,
+            effective_from: self.effective_from.to_owned(),
+            effective_to: self.effective_to.to_owned(),
+            rollover: self.rollover_policy(),
+            rollover_cap: self.rollover_cap,
+        }
+    }
+
+    /// Unknown values are read as no rollover rather than failing the whole budget list.
+    pub fn rollover_policy(&self) -> RolloverPolicy {
+        self.rollover.parse().unwrap_or_default()
+    }
+
+    /// What this month leaves for the next one, given the `leftover` of limit plus carried
+    /// minus spent.
+    pub fn carry(&self, leftover: f32) -> f32 {
+        let carried = match self.rollover_policy() {
+            RolloverPolicy::None => 0.0,
+            RolloverPolicy::CarrySurplus => leftover.max(0.0),
+            RolloverPolicy::CarryBoth => leftover,
+        };
+
+        match self.rollover_cap {
+            Some(cap) => carried.clamp(-cap, cap),
+            None => carried,
+        }
+    }
+
+    /// `month` is `YYYY-MM`, which compares correctly as text against the bounds.
+    pub fn covers(&self, month: &str) -> bool {
+        self.effective_from
+            .as_deref()
+            .is_none_or(|from| from <= month)
+            && self.effective_to.as_deref().is_none_or(|to| month <= to)
+    }
+}
+
+#[derive(Debug, Clone, Queryable, Insertable, AsChangeset)]
+#[diesel(table_name = budgets, treat_none_as_null = true)]
+pub struct SetBudgetDto {
+    pub category: String,
+    pub monthly_limit: f32,
+    pub effective_from: Option<String>,
+    pub effective_to: Option<String>,
+    pub rollover: String,
+    pub rollover_cap: Option<f32>,
+}
+
+/// Expenses per budgeted category and `YYYY-MM` month, reported as positive spending.
+#[derive(Debug, Clone, QueryableByName)]
+pub struct MonthlySpent {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub category: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub month: String,
+    #[diesel(sql_type = diesel::sql_types::Float)]
+    pub spent: f32,
+}
@@ -0,0 +1,23 @@
+use diesel::prelude::*;
+
+use crate::infrastructure::database::schema::envelope_transfers;
+
+/// `None` on either side is the unassigned pool.
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = envelope_transfers)]
+pub struct EnvelopeTransfer {
+    pub id: i32,
+    pub from_envelope: Option<String>,
+    pub to_envelope: Option<String>,
+    pub amount: f32,
+    pub date: String,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable)]
+#[diesel(table_name = envelope_transfers)]
+pub struct AddEnvelopeTransferDto {
+    pub from_envelope: Option<String>,
+    pub to_envelope: Option<String>,
+    pub amount: f32,
+    pub date: String,
+}
@@ -1,3 +1,6 @@
+pub mod bill_occurrences;
+pub mod budgets;
+pub mod envelope_transfers;
 pub mod monthly_spending;
 pub mod my_ledger;
 pub mod tax_deductions_list;
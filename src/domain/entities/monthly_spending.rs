@@ -1,7 +1,9 @@
 use diesel::prelude::*;
 
 use crate::{
-    domain::value_objects::spending_scanner::MonthlySpendingModel,
+    domain::value_objects::{
+        bills::PostingMode, recurrence::RecurrenceModel, spending_scanner::MonthlySpendingModel,
+    },
     infrastructure::database::schema::monthly_spending,
 };
 
@@ -11,7 +13,14 @@ pub struct MonthlySpending {
     pub id: i32,
     pub title: String,
     pub amount: f32,
-    pub due_date: String,
+    pub frequency: String,
+    pub interval: i32,
+    pub anchor_date: String,
+    pub end_date: Option<String>,
+    pub count: Option<i32>,
+    pub category: String,
+    pub posting: String,
+    pub post_from: String,
 }
 
 impl MonthlySpending {
@@ -20,15 +29,40 @@ impl MonthlySpending {
             id: self.id,
             title: self.title.to_owned(),
             amount: self.amount,
-            due_date: self.due_date.to_owned(),
+            recurrence: self.recurrence(),
+            category: self.category.to_owned(),
+            posting: self.posting_mode(),
+        }
+    }
+
+    /// Unknown modes are read as confirm-first, so nothing is posted without asking.
+    pub fn posting_mode(&self) -> PostingMode {
+        self.posting.parse().unwrap_or_default()
+    }
+
+    /// Unknown frequencies are read as monthly, which is what every item used to be.
+    pub fn recurrence(&self) -> RecurrenceModel {
+        RecurrenceModel {
+            frequency: self.frequency.parse().unwrap_or_default(),
+            interval: self.interval.max(1) as u32,
+            anchor_date: self.anchor_date.to_owned(),
+            end_date: self.end_date.to_owned(),
+            count: self.count.map(|c| c.max(0) as u32),
         }
     }
 }
 
-#[derive(Debug, Clone, Queryable, Insertable)]
-#[diesel(table_name = monthly_spending)]
+#[derive(Debug, Clone, Queryable, Insertable, AsChangeset)]
+#[diesel(table_name = monthly_spending, treat_none_as_null = true)]
 pub struct AddMonthlySpendingDto {
     pub title: String,
     pub amount: f32,
-    pub due_date: String,
+    pub frequency: String,
+    pub interval: i32,
+    pub anchor_date: String,
+    pub end_date: Option<String>,
+    pub count: Option<i32>,
+    pub category: String,
+    pub posting: String,
+    pub post_from: String,
 }
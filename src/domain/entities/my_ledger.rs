@@ -1,7 +1,11 @@
 use crate::{
-    domain::value_objects::spending_scanner::SpendingScannerModel,
+    domain::value_objects::{
+        spending_scanner::{MerchantSummaryModel, SpendingScannerModel, TransactionMatchModel},
+        tax_simulator::{IncomeModel, IncomeType},
+    },
     infrastructure::database::schema::my_ledger,
 };
+use chrono::NaiveDate;
 use diesel::prelude::*;
 
 #[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
@@ -24,6 +28,13 @@ impl MyLedger {
             date: self.date.to_owned(),
         }
     }
+
+    /// Ledger dates are stored as text, only the `YYYY-MM-DD` part is meaningful here.
+    pub fn naive_date(&self) -> Option<NaiveDate> {
+        self.date
+            .get(..10)
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    }
 }
 
 #[derive(Debug, Clone, Queryable, Insertable)]
@@ -33,4 +44,128 @@ pub struct RecordMyLedgerDto {
     pub category: String,
     pub description: String,
     pub date: String,
+    pub income_type: Option<String>,
+}
+
+/// Income entry with its section 40 type, as read for the tax simulation.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = my_ledger)]
+pub struct IncomeLedger {
+    pub id: i32,
+    pub amount: f32,
+    pub category: String,
+    pub description: String,
+    pub date: String,
+    pub income_type: Option<String>,
+}
+
+impl IncomeLedger {
+    /// Income recorded before types existed, or without one, counts as salary.
+    pub fn income_type(&self) -> IncomeType {
+        self.income_type
+            .as_deref()
+            .and_then(|t| t.parse().ok())
+            .unwrap_or_default()
+    }
+
+    pub fn to_model(&self) -> IncomeModel {
+        let income_type = self.income_type();
+
+        IncomeModel {
+            id: self.id,
+            amount: self.amount,
+            category: self.category.to_owned(),
+            description: self.description.to_owned(),
+            date: self.date.to_owned(),
+            income_type,
+            section: income_type.section().to_string(),
+        }
+    }
+}
+
+/// Income of a year summed per stored `income_type`, as aggregated by the database.
+#[derive(Debug, Clone, Queryable)]
+pub struct IncomeTypeTotal {
+    pub income_type: Option<String>,
+    pub total: Option<f32>,
+}
+
+impl IncomeTypeTotal {
+    /// Same fallback as [`IncomeLedger::income_type`], untyped income counts as salary.
+    pub fn income_type(&self) -> IncomeType {
+        self.income_type
+            .as_deref()
+            .and_then(|t| t.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Sum of amounts per category, as aggregated by the database.
+#[derive(Debug, Clone, Queryable)]
+pub struct CategoryTotal {
+    pub category: String,
+    pub total: Option<f32>,
+}
+
+/// Expenses and income per category kept apart, so a refund or salary does not cancel out
+/// spending. `expense` is the sum of the negative amounts and stays negative.
+#[derive(Debug, Clone, Queryable)]
+pub struct CategoryCashFlow {
+    pub category: String,
+    pub expense: Option<f32>,
+    pub income: Option<f32>,
+}
+
+/// Expenses grouped by description, as aggregated by the database.
+#[derive(Debug, Clone, Queryable)]
+pub struct MerchantSummary {
+    pub description: String,
+    pub count: i64,
+    pub total: Option<f32>,
+    pub average: Option<f64>,
+}
+
+impl MerchantSummary {
+    /// Expenses are stored as negative amounts, the model reports them as positive spending.
+    pub fn to_model(&self) -> MerchantSummaryModel {
+        MerchantSummaryModel {
+            description: self.description.to_owned(),
+            count: self.count,
+            total: -self.total.unwrap_or(0.0),
+            average: -self.average.unwrap_or(0.0) as f32,
+        }
+    }
+}
+
+/// Full-text search hit from `my_ledger_fts`, lower `rank` is a better match.
+#[derive(Debug, Clone, QueryableByName)]
+pub struct TransactionMatch {
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub id: i32,
+    #[diesel(sql_type = diesel::sql_types::Float)]
+    pub amount: f32,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub category: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub description: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub date: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub snippet: String,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    pub rank: f64,
+}
+
+impl TransactionMatch {
+    pub fn to_model(&self) -> TransactionMatchModel {
+        TransactionMatchModel {
+            id: self.id,
+            amount: self.amount,
+            category: self.category.to_owned(),
+            description: self.description.to_owned(),
+            date: self.date.to_owned(),
+            snippet: self.snippet.to_owned(),
+            rank: self.rank as f32,
+        }
+    }
 }
@@ -0,0 +1,39 @@
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::domain::entities::{
+    bill_occurrences::{AddBillOccurrenceDto, BillOccurrence, SettleBillOccurrenceDto},
+    my_ledger::RecordMyLedgerDto,
+};
+
+#[async_trait::async_trait]
+#[mockall::automock]
+pub trait BillRepository {
+    /// Occurrences that are already known are left untouched.
+    async fn add_occurrences(
+        &self,
+        add_bill_occurrence_dtos: Vec<AddBillOccurrenceDto>,
+    ) -> Result<()>;
+    /// Due date of the latest occurrence known for each item, by item id.
+    async fn latest_due_dates(&self) -> Result<HashMap<i32, String>>;
+    async fn find_occurrence(
+        &self,
+        monthly_spending_id: i32,
+        due_date: String,
+    ) -> Result<Option<BillOccurrence>>;
+    /// Pending occurrences due on or before `until`, oldest first.
+    async fn view_pending_occurrences(&self, until: String) -> Result<Vec<BillOccurrence>>;
+    /// Records the ledger entry and marks the occurrence paid on the entry's date in one go.
+    /// `None` when the occurrence is no longer pending, in which case nothing is recorded.
+    async fn post_occurrence(
+        &self,
+        occurrence_id: i32,
+        record_my_ledger_dto: RecordMyLedgerDto,
+    ) -> Result<Option<i32>>;
+    /// `false` when the occurrence is no longer pending, in which case nothing changes.
+    async fn settle_occurrence(
+        &self,
+        occurrence_id: i32,
+        settle_bill_occurrence_dto: SettleBillOccurrenceDto,
+    ) -> Result<bool>;
+}
@@ -0,0 +1,13 @@
+use anyhow::Result;
+
+use crate::domain::entities::budgets::{Budget, MonthlySpent, SetBudgetDto};
+
+#[async_trait::async_trait]
+#[mockall::automock]
+pub trait BudgetRepository {
+    async fn view_all_budgets(&self) -> Result<Vec<Budget>>;
+    /// Replaces the budget with the same category and effective period, or adds a new one.
+    async fn set_budget(&self, set_budget_dto: SetBudgetDto) -> Result<i32>;
+    /// Spending per month for every budgeted category, for dates before `before`.
+    async fn monthly_spent(&self, before: String) -> Result<Vec<MonthlySpent>>;
+}
@@ -0,0 +1,16 @@
+use anyhow::Result;
+
+use crate::domain::entities::envelope_transfers::{AddEnvelopeTransferDto, EnvelopeTransfer};
+
+#[async_trait::async_trait]
+#[mockall::automock]
+pub trait EnvelopeRepository {
+    async fn view_all_transfers(&self) -> Result<Vec<EnvelopeTransfer>>;
+    /// Records the transfer only if its source, the unassigned pool when `from_envelope` is
+    /// `None`, still holds the amount, checked in the same transaction so two transfers cannot
+    /// both spend the same money. `None` when it does not, in which case nothing is recorded.
+    async fn add_transfer(
+        &self,
+        add_envelope_transfer_dto: AddEnvelopeTransferDto,
+    ) -> Result<Option<i32>>;
+}
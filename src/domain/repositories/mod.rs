@@ -1,3 +1,6 @@
+pub mod bills;
+pub mod budget;
 pub mod cash_flow;
+pub mod envelope;
 pub mod spending_scanner;
 pub mod tax_simulator;
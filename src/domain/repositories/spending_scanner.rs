@@ -1,10 +1,15 @@
 use anyhow::Result;
 
-use crate::domain::entities::{
-    monthly_spending::{AddMonthlySpendingDto, MonthlySpending},
-    my_ledger::MyLedger,
+use crate::domain::{
+    entities::{
+        monthly_spending::{AddMonthlySpendingDto, MonthlySpending},
+        my_ledger::{CategoryCashFlow, CategoryTotal, MerchantSummary, MyLedger, TransactionMatch},
+    },
+    value_objects::spending_scanner::MerchantRanking,
 };
 
+/// Date ranges are half-open, `start` is included and `end` is not, so an entry stored with a
+/// time of day still falls on its day.
 #[async_trait::async_trait]
 #[mockall::automock]
 pub trait SpendingScannerRepository {
@@ -13,10 +18,41 @@ pub trait SpendingScannerRepository {
     async fn this_year(&self) -> Result<Vec<MyLedger>>;
     async fn lifetime(&self) -> Result<Vec<MyLedger>>;
     async fn custom(&self, start: String, end: String) -> Result<Vec<MyLedger>>;
+    async fn sum_by_category(&self, start: String, end: String) -> Result<Vec<CategoryTotal>>;
+    async fn cash_flow_by_category(
+        &self,
+        start: String,
+        end: String,
+    ) -> Result<Vec<CategoryCashFlow>>;
+    async fn top_expenses(&self, start: String, end: String, limit: i64) -> Result<Vec<MyLedger>>;
+    async fn top_merchants(
+        &self,
+        start: String,
+        end: String,
+        limit: i64,
+        rank_by: MerchantRanking,
+    ) -> Result<Vec<MerchantSummary>>;
+    async fn search(
+        &self,
+        query: String,
+        start: String,
+        end: String,
+        category: Option<String>,
+        limit: i64,
+    ) -> Result<Vec<TransactionMatch>>;
     async fn view_all_monthly_spending(&self) -> Result<Vec<MonthlySpending>>;
     async fn add_monthly_spending(
         &self,
         add_monthly_spending_model: AddMonthlySpendingDto,
     ) -> Result<i32>;
+    /// Replaces every field of the item. With `reschedule_from`, its pending bill cycles due on or
+    /// after that day are dropped since they were worked out from the old schedule. Earlier
+    /// ones are overdue and still owed.
+    async fn update_monthly_spending(
+        &self,
+        id: i32,
+        update_monthly_spending_dto: AddMonthlySpendingDto,
+        reschedule_from: Option<String>,
+    ) -> Result<()>;
     async fn remove_monthly_spending(&self, id: i32) -> Result<()>;
 }
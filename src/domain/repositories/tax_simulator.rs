@@ -1,5 +1,5 @@
 use crate::domain::entities::{
-    my_ledger::MyLedger,
+    my_ledger::{IncomeLedger, IncomeTypeTotal},
     tax_deductions_list::{AddTaxDeductionsListDto, TaxDeductionsList},
 };
 use anyhow::Result;
@@ -7,7 +7,8 @@ use anyhow::Result;
 #[async_trait::async_trait]
 #[mockall::automock]
 pub trait TaxSimulatorRepository {
-    async fn view_all_income_by_year(&self, year: i32) -> Result<Vec<MyLedger>>;
+    async fn view_all_income_by_year(&self, year: i32) -> Result<Vec<IncomeLedger>>;
+    async fn income_by_type(&self, year: i32) -> Result<Vec<IncomeTypeTotal>>;
     async fn view_all_tax_deductions_list(&self) -> Result<Vec<TaxDeductionsList>>;
     async fn add_tax_deduction_list(
         &self,
@@ -0,0 +1,157 @@
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_BILL_CATEGORY: &str = "BILLS";
+
+/// Whether due occurrences of a scheduled payment go into the ledger on their own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub enum PostingMode {
+    /// Posted as soon as the occurrence is due.
+    Auto,
+    /// Waits until the occurrence is confirmed through `post_due_bills`.
+    #[default]
+    ConfirmFirst,
+}
+
+impl PostingMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PostingMode::Auto => "Auto",
+            PostingMode::ConfirmFirst => "ConfirmFirst",
+        }
+    }
+}
+
+impl FromStr for PostingMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "Auto" => Ok(PostingMode::Auto),
+            "ConfirmFirst" => Ok(PostingMode::ConfirmFirst),
+            _ => Err(anyhow!("Unknown posting mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub enum BillStatus {
+    #[default]
+    Pending,
+    Paid,
+    Skipped,
+}
+
+impl BillStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BillStatus::Pending => "Pending",
+            BillStatus::Paid => "Paid",
+            BillStatus::Skipped => "Skipped",
+        }
+    }
+}
+
+impl FromStr for BillStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "Pending" => Ok(BillStatus::Pending),
+            "Paid" => Ok(BillStatus::Paid),
+            "Skipped" => Ok(BillStatus::Skipped),
+            _ => Err(anyhow!("Unknown bill status: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct PostDueBillsModel {
+    /// Occurrence ids of confirm-first bills to post now, leave empty to only post automatic ones.
+    #[serde(default)]
+    pub confirm: Vec<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BillOccurrenceModel {
+    pub id: i32,
+    pub monthly_spending_id: i32,
+    pub title: String,
+    pub category: String,
+    pub amount: f32,
+    pub due_date: String,
+    pub status: BillStatus,
+    pub paid_date: Option<String>,
+    pub ledger_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostDueBillsResultModel {
+    pub posted: Vec<BillOccurrenceModel>,
+    pub awaiting_confirmation: Vec<BillOccurrenceModel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct MarkBillPaidModel {
+    pub monthly_spending_id: i32,
+    /// Cycle to mark (format: YYYY-MM-DD), leave empty for the oldest unpaid one.
+    pub due_date: Option<String>,
+    /// When it was paid (format: YYYY-MM-DD), leave empty for today.
+    pub paid_date: Option<String>,
+    /// Ledger transaction that paid the bill, leave empty to record a new one.
+    pub ledger_id: Option<i32>,
+    /// Mark the cycle as skipped instead, nothing is recorded in the ledger.
+    #[serde(default)]
+    pub skip: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnpaidBillModel {
+    #[serde(flatten)]
+    pub bill: BillOccurrenceModel,
+    pub overdue: bool,
+    pub days_overdue: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnpaidBillsModel {
+    pub unpaid_total: f32,
+    pub overdue_total: f32,
+    pub bills: Vec<UnpaidBillModel>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct UpcomingBillsFilter {
+    /// How many days ahead to look, today included (default: 30, at most 366).
+    pub days: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpcomingBillModel {
+    pub monthly_spending_id: i32,
+    pub title: String,
+    pub category: String,
+    pub amount: f32,
+    pub due_date: String,
+    /// Sum of this payment and every one before it in the list.
+    pub running_total: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpcomingBillsModel {
+    pub start: String,
+    pub end: String,
+    pub total: f32,
+    pub bills: Vec<UpcomingBillModel>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ExportBillsCalendarModel {
+    /// Where to write the .ics file, relative to the server's export directory (default: bills.ics).
+    pub path: Option<String>,
+    /// How many days before the due date the reminder goes off, 0 for on the day (default: 1).
+    pub reminder_days: Option<u32>,
+}
@@ -0,0 +1,125 @@
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::entities::budgets::SetBudgetDto;
+
+/// What happens to the money left over, or overspent, at the end of a month.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub enum RolloverPolicy {
+    #[default]
+    None,
+    /// Unspent budget is added to next month, overspending is forgiven.
+    CarrySurplus,
+    /// Unspent budget is added to next month, overspending is taken from it.
+    CarryBoth,
+}
+
+impl RolloverPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RolloverPolicy::None => "None",
+            RolloverPolicy::CarrySurplus => "CarrySurplus",
+            RolloverPolicy::CarryBoth => "CarryBoth",
+        }
+    }
+}
+
+impl FromStr for RolloverPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "None" => Ok(RolloverPolicy::None),
+            "CarrySurplus" => Ok(RolloverPolicy::CarrySurplus),
+            "CarryBoth" => Ok(RolloverPolicy::CarryBoth),
+            _ => Err(anyhow!("Unknown rollover policy: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct SetBudgetModel {
+    pub category: String,
+    pub monthly_limit: f32,
+    /// First month the budget applies to (format: YYYY-MM), leave empty for no start.
+    pub effective_from: Option<String>,
+    /// Last month the budget applies to (format: YYYY-MM), leave empty for no end.
+    pub effective_to: Option<String>,
+    #[serde(default)]
+    pub rollover: RolloverPolicy,
+    /// Largest amount carried into the next month either way, leave empty for no cap.
+    pub rollover_cap: Option<f32>,
+}
+
+impl SetBudgetModel {
+    pub fn to_dto(&self) -> SetBudgetDto {
+        SetBudgetDto {
+            category: self.category.to_owned().to_uppercase(),
+            monthly_limit: self.monthly_limit,
+            effective_from: self.effective_from.to_owned(),
+            effective_to: self.effective_to.to_owned(),
+            rollover: self.rollover.as_str().to_string(),
+            rollover_cap: self.rollover_cap,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetModel {
+    pub id: i32,
+    pub category: String,
+    pub monthly_limit: f32,
+    pub effective_from: Option<String>,
+    pub effective_to: Option<String>,
+    pub rollover: RolloverPolicy,
+    pub rollover_cap: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct BudgetStatusFilter {
+    /// Month to check (format: YYYY-MM), leave empty for the current month.
+    pub month: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStatusModel {
+    pub category: String,
+    pub monthly_limit: f32,
+    /// Brought forward from the previous months by the rollover policy, negative for a deficit.
+    pub carried: f32,
+    /// Monthly limit plus carried.
+    pub available: f32,
+    pub spent: f32,
+    /// Negative once the budget is overspent.
+    pub remaining: f32,
+    /// Share of `available` spent. A month that starts with nothing available is fully used.
+    pub percent_used: f32,
+    /// Spending at the current daily pace by the end of the month. Past months report what was
+    /// actually spent.
+    pub projected: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthlyBudgetStatusModel {
+    pub month: String,
+    pub days_elapsed: u32,
+    pub days_in_month: u32,
+    pub budgets: Vec<BudgetStatusModel>,
+}
+
+/// Raised after recording an expense that takes its category to a budget threshold.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetWarningModel {
+    pub category: String,
+    pub month: String,
+    /// Highest threshold this expense crossed, as a percentage of the available budget.
+    pub threshold: f32,
+    pub percent_used: f32,
+    pub available: f32,
+    pub spent: f32,
+    /// Negative once the budget is overspent.
+    pub remaining: f32,
+}
@@ -1,13 +1,22 @@
 use rmcp::schemars;
 use serde::{Deserialize, Serialize};
 
-use crate::domain::entities::my_ledger::RecordMyLedgerDto;
+use crate::domain::{
+    entities::my_ledger::RecordMyLedgerDto,
+    value_objects::{
+        budget::BudgetWarningModel, tax_simulator::IncomeType,
+        unusual_spending::UnusualTransactionModel,
+    },
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct RecordCashFlowModel {
     pub amount: f32,
     pub category: String,
     pub description: String,
+    /// Section 40 type of an income entry, ignored for expenses.
+    #[serde(default)]
+    pub income_type: Option<IncomeType>,
 }
 
 impl RecordCashFlowModel {
@@ -17,6 +26,7 @@ impl RecordCashFlowModel {
             category: self.category.to_owned().to_uppercase(),
             description: self.description.to_owned(),
             date: chrono::Utc::now().naive_utc().date().to_string(),
+            income_type: income_type(self.amount, self.income_type),
         }
     }
 }
@@ -27,6 +37,9 @@ pub struct RecordCashFlowWithDateModel {
     pub category: String,
     pub description: String,
     pub date: String,
+    /// Section 40 type of an income entry, ignored for expenses.
+    #[serde(default)]
+    pub income_type: Option<IncomeType>,
 }
 
 impl RecordCashFlowWithDateModel {
@@ -36,6 +49,20 @@ impl RecordCashFlowWithDateModel {
             category: self.category.to_owned().to_uppercase(),
             description: self.description.to_owned(),
             date: self.date.to_string(),
+            income_type: income_type(self.amount, self.income_type),
         }
     }
 }
+
+fn income_type(amount: f32, income_type: Option<IncomeType>) -> Option<String> {
+    income_type
+        .filter(|_| amount > 0.0)
+        .map(|t| t.as_str().to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+pub enum CashFlowWarningModel {
+    UnusualSpending(UnusualTransactionModel),
+    BudgetThreshold(BudgetWarningModel),
+}
@@ -0,0 +1,51 @@
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::value_objects::spending_scanner::{Grouping, Range};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub enum ChartKind {
+    Pie,
+    Bar,
+    Line,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub enum ChartFormat {
+    #[default]
+    Png,
+    Svg,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct SpendingChartModel {
+    pub filter: Range,
+    pub kind: ChartKind,
+    /// Chart the spending over time instead of by category.
+    pub group_by: Option<Grouping>,
+    #[serde(default)]
+    pub format: ChartFormat,
+}
+
+/// `Text` draws the result as a Unicode chart that terminal clients can show as is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Text,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct SpendingVisualizerModel {
+    pub filter: Range,
+    #[serde(default)]
+    pub output: OutputFormat,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct SpendingTimeSeriesModel {
+    pub filter: Range,
+    pub group_by: Grouping,
+    #[serde(default)]
+    pub output: OutputFormat,
+}
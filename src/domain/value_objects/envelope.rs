@@ -0,0 +1,37 @@
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct AssignEnvelopeModel {
+    /// Envelopes are named after the ledger category they pay for.
+    pub envelope: String,
+    pub amount: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct MoveEnvelopeModel {
+    pub from_envelope: String,
+    pub to_envelope: String,
+    pub amount: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvelopeBalanceModel {
+    pub envelope: String,
+    /// Net amount assigned or moved into the envelope.
+    pub assigned: f32,
+    /// Spending in the category since money was first put into the envelope.
+    pub spent: f32,
+    /// Negative once the envelope is overspent.
+    pub available: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvelopeStatusModel {
+    pub income: f32,
+    /// Income not yet assigned to an envelope, less spending no envelope paid for.
+    pub unassigned: f32,
+    /// Spending in categories without an envelope, or from before their envelope was opened.
+    pub unenveloped_spent: f32,
+    pub envelopes: Vec<EnvelopeBalanceModel>,
+}
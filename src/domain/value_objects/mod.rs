@@ -1,3 +1,11 @@
+pub mod bills;
+pub mod budget;
 pub mod cash_flow;
+pub mod chart;
+pub mod envelope;
+pub mod recurrence;
 pub mod spending_scanner;
+pub mod subscription;
 pub mod tax_simulator;
+pub mod unusual_spending;
+pub mod validation;
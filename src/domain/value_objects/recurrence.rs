@@ -0,0 +1,308 @@
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Days, Months, NaiveDate};
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::value_objects::validation::ValidationErrors;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    #[default]
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "Daily",
+            Frequency::Weekly => "Weekly",
+            Frequency::Monthly => "Monthly",
+            Frequency::Yearly => "Yearly",
+        }
+    }
+}
+
+impl FromStr for Frequency {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "Daily" => Ok(Frequency::Daily),
+            "Weekly" => Ok(Frequency::Weekly),
+            "Monthly" => Ok(Frequency::Monthly),
+            "Yearly" => Ok(Frequency::Yearly),
+            _ => Err(anyhow!("Unknown frequency: {}", s)),
+        }
+    }
+}
+
+/// Repeats every `interval` days, weeks, months or years from `anchor_date`. Monthly and yearly
+/// occurrences keep the anchor's day and fall back to the last day of shorter months, so a bill
+/// anchored on the 31st is due on Feb 28 and back on Mar 31.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct RecurrenceModel {
+    pub frequency: Frequency,
+    /// Every how many days, weeks, months or years (default: 1).
+    #[serde(default = "default_interval")]
+    pub interval: u32,
+    /// First occurrence (format: YYYY-MM-DD).
+    pub anchor_date: String,
+    /// Last day an occurrence may fall on (format: YYYY-MM-DD), leave empty for no end.
+    pub end_date: Option<String>,
+    /// Total number of occurrences counted from the anchor, leave empty for no limit.
+    pub count: Option<u32>,
+}
+
+fn default_interval() -> u32 {
+    1
+}
+
+impl RecurrenceModel {
+    /// Monthly on `day` of the month, anchored in the month of `today`, or the latest month before
+    /// it that has that day. Used for the `DD` due dates monthly spending started with.
+    pub fn monthly_on_day(day: u32, today: NaiveDate) -> Result<Self> {
+        if !(1..=31).contains(&day) {
+            return Err(anyhow!("Due day must be between 1 and 31, got {}", day));
+        }
+
+        let anchor_date = (0..12)
+            .filter_map(|back| today.checked_sub_months(Months::new(back)))
+            .find_map(|month| month.with_day(day))
+            .ok_or_else(|| anyhow!("No month with day {} near {}", day, today))?;
+
+        Ok(Self {
+            frequency: Frequency::Monthly,
+            interval: 1,
+            anchor_date: anchor_date.to_string(),
+            end_date: None,
+            count: None,
+        })
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut errors = ValidationErrors::default();
+        self.check(&mut errors);
+        errors.into_result()
+    }
+
+    /// Adds every problem with the recurrence to `errors`, fields prefixed with `recurrence.`.
+    pub fn check(&self, errors: &mut ValidationErrors) {
+        if self.interval == 0 {
+            errors.add("recurrence.interval", "Must be at least 1".to_string());
+        }
+        if self.count == Some(0) {
+            errors.add(
+                "recurrence.count",
+                "Must be at least 1 when given".to_string(),
+            );
+        }
+
+        let anchor = self.anchor();
+        if let Err(e) = &anchor {
+            errors.add("recurrence.anchor_date", e.to_string());
+        }
+        match (anchor, self.end()) {
+            (_, Err(e)) => errors.add("recurrence.end_date", e.to_string()),
+            (Ok(anchor), Ok(Some(end))) if end < anchor => errors.add(
+                "recurrence.end_date",
+                format!("{} is before anchor_date {}", end, anchor),
+            ),
+            _ => {}
+        }
+    }
+
+    pub fn anchor(&self) -> Result<NaiveDate> {
+        parse_date(&self.anchor_date)
+    }
+
+    pub fn end(&self) -> Result<Option<NaiveDate>> {
+        self.end_date.as_deref().map(parse_date).transpose()
+    }
+
+    /// The `n`th occurrence counting from zero at the anchor. Always computed from the anchor
+    /// rather than the previous occurrence, so a clamped Feb 28 does not drag later months.
+    pub fn nth(&self, anchor: NaiveDate, n: u32) -> Option<NaiveDate> {
+        let steps = n.checked_mul(self.interval)?;
+
+        match self.frequency {
+            Frequency::Daily => anchor.checked_add_days(Days::new(steps as u64)),
+            Frequency::Weekly => anchor.checked_add_days(Days::new(steps as u64 * 7)),
+            Frequency::Monthly => anchor.checked_add_months(Months::new(steps)),
+            Frequency::Yearly => anchor.checked_add_months(Months::new(steps.checked_mul(12)?)),
+        }
+    }
+
+    /// Every occurrence from `start` to `end`, both inclusive, in date order.
+    pub fn occurrences(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<NaiveDate>> {
+        let anchor = self.anchor()?;
+        let last = match self.end()? {
+            Some(end_date) => end.min(end_date),
+            None => end,
+        };
+
+        let mut occurrences = Vec::new();
+        let mut n = self.first_index_near(anchor, start);
+
+        while self.count.is_none_or(|count| n < count) {
+            let Some(date) = self.nth(anchor, n) else {
+                break;
+            };
+            if date > last {
+                break;
+            }
+            if date >= start {
+                occurrences.push(date);
+            }
+            n += 1;
+        }
+
+        Ok(occurrences)
+    }
+
+    /// An index at or just before the first occurrence on or after `start`, so long running
+    /// schedules do not have to be walked from the anchor.
+    fn first_index_near(&self, anchor: NaiveDate, start: NaiveDate) -> u32 {
+        if start <= anchor {
+            return 0;
+        }
+
+        let elapsed = match self.frequency {
+            Frequency::Daily => (start - anchor).num_days(),
+            Frequency::Weekly => (start - anchor).num_days() / 7,
+            Frequency::Monthly => months_between(anchor, start),
+            Frequency::Yearly => months_between(anchor, start) / 12,
+        };
+
+        // One step back covers month end clamping
+        (elapsed / self.interval as i64 - 1).clamp(0, u32::MAX as i64) as u32
+    }
+}
+
+fn months_between(from: NaiveDate, to: NaiveDate) -> i64 {
+    (to.year() as i64 - from.year() as i64) * 12 + to.month() as i64 - from.month() as i64
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| anyhow!("Invalid date, expected YYYY-MM-DD: {}", date))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::domain::value_objects::recurrence::{Frequency, RecurrenceModel};
+
+    fn date(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+    }
+
+    fn recurrence(frequency: Frequency, interval: u32, anchor_date: &str) -> RecurrenceModel {
+        RecurrenceModel {
+            frequency,
+            interval,
+            anchor_date: anchor_date.to_string(),
+            end_date: None,
+            count: None,
+        }
+    }
+
+    /// Every occurrence in the range found by walking from the anchor, without any shortcut.
+    fn walked(recurrence: &RecurrenceModel, start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+        let anchor = recurrence.anchor().unwrap();
+
+        (0..)
+            .map_while(|n| recurrence.nth(anchor, n).filter(|d| *d <= end))
+            .filter(|d| *d >= start)
+            .collect()
+    }
+
+    #[test]
+    fn test_month_end_anchor_clamps_without_drifting() {
+        let monthly = recurrence(Frequency::Monthly, 1, "2024-01-31");
+
+        let occurrences = monthly
+            .occurrences(date("2024-01-01"), date("2025-03-31"))
+            .unwrap();
+
+        assert_eq!(occurrences.len(), 15);
+        assert_eq!(occurrences[1], date("2024-02-29"));
+        assert_eq!(occurrences[2], date("2024-03-31"));
+        assert_eq!(occurrences[3], date("2024-04-30"));
+        assert_eq!(occurrences[13], date("2025-02-28"));
+        assert_eq!(occurrences[14], date("2025-03-31"));
+
+        let yearly = recurrence(Frequency::Yearly, 1, "2024-02-29");
+        let anchor = yearly.anchor().unwrap();
+
+        assert_eq!(yearly.nth(anchor, 1), Some(date("2025-02-28")));
+        assert_eq!(yearly.nth(anchor, 4), Some(date("2028-02-29")));
+    }
+
+    #[test]
+    fn test_start_far_after_anchor_matches_walking_from_anchor() {
+        let schedules = [
+            recurrence(Frequency::Monthly, 3, "2020-01-31"),
+            recurrence(Frequency::Monthly, 2, "2019-08-30"),
+            recurrence(Frequency::Weekly, 2, "2020-01-06"),
+            recurrence(Frequency::Daily, 10, "2020-01-01"),
+            recurrence(Frequency::Yearly, 2, "2016-02-29"),
+        ];
+        let starts = ["2025-04-30", "2025-05-01", "2025-02-28", "2025-03-01"];
+
+        for schedule in &schedules {
+            for start in starts {
+                let start = date(start);
+                let end = date("2026-12-31");
+
+                assert_eq!(
+                    schedule.occurrences(start, end).unwrap(),
+                    walked(schedule, start, end),
+                    "{:?} from {}",
+                    schedule,
+                    start
+                );
+            }
+        }
+
+        // Quarterly from Jan 31: April clamps to the 30th, which is the start itself
+        let quarterly = &schedules[0];
+        assert_eq!(
+            quarterly
+                .occurrences(date("2025-04-30"), date("2025-10-31"))
+                .unwrap(),
+            vec![date("2025-04-30"), date("2025-07-31"), date("2025-10-31")]
+        );
+    }
+
+    #[test]
+    fn test_count_and_end_date_stop_at_whichever_comes_first() {
+        let mut monthly = recurrence(Frequency::Monthly, 1, "2025-01-15");
+        let (start, end) = (date("2025-01-01"), date("2025-12-31"));
+
+        monthly.count = Some(5);
+        monthly.end_date = Some("2025-03-31".to_string());
+        assert_eq!(monthly.occurrences(start, end).unwrap().len(), 3);
+
+        monthly.count = Some(2);
+        monthly.end_date = Some("2025-12-31".to_string());
+        assert_eq!(
+            monthly.occurrences(start, end).unwrap(),
+            vec![date("2025-01-15"), date("2025-02-15")]
+        );
+
+        // The count is from the anchor, not from the start of the range
+        monthly.count = Some(3);
+        assert!(
+            monthly
+                .occurrences(date("2025-06-01"), end)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
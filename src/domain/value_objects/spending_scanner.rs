@@ -1,7 +1,16 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, Months, NaiveDate};
 use rmcp::schemars;
 use serde::{Deserialize, Serialize};
 
-use crate::domain::entities::monthly_spending::AddMonthlySpendingDto;
+use crate::domain::{
+    entities::monthly_spending::AddMonthlySpendingDto,
+    value_objects::{
+        bills::{DEFAULT_BILL_CATEGORY, PostingMode},
+        recurrence::RecurrenceModel,
+    },
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct SpendingScannerFilter {
@@ -27,23 +36,278 @@ pub enum Range {
     Custom { start: String, end: String },
 }
 
+impl Range {
+    /// Concrete first and last day of the range, `None` for `Lifetime`.
+    pub fn bounds(&self, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+        match self {
+            Range::Today => Some((today, today)),
+            Range::ThisMonth => Some(Grouping::Month.bucket_bounds(today)),
+            Range::ThisYear => Some(Grouping::Year.bucket_bounds(today)),
+            Range::Lifetime => None,
+            Range::Custom { start, end } => {
+                let start = NaiveDate::parse_from_str(start, "%Y-%m-%d").ok()?;
+                let end = NaiveDate::parse_from_str(end, "%Y-%m-%d").ok()?;
+                Some((start, end))
+            }
+        }
+    }
+
+    /// The period of the same kind right before this one: yesterday, last month, last year,
+    /// or for a custom range, the same number of days ending the day before it starts.
+    pub fn previous_period(&self, today: NaiveDate) -> Option<Range> {
+        let (start, end) = match self {
+            Range::Today => {
+                let yesterday = today - Days::new(1);
+                (yesterday, yesterday)
+            }
+            Range::ThisMonth => Grouping::Month.bucket_bounds(today - Months::new(1)),
+            Range::ThisYear => Grouping::Year.bucket_bounds(today - Months::new(12)),
+            Range::Lifetime => return None,
+            Range::Custom { .. } => {
+                let (start, end) = self.bounds(today)?;
+                let previous_end = start - Days::new(1);
+                let previous_start = previous_end - (end - start);
+                (previous_start, previous_end)
+            }
+        };
+
+        Some(Range::Custom {
+            start: start.to_string(),
+            end: end.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub enum Grouping {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl Grouping {
+    /// First and last day of the bucket that `date` falls into. Weeks start on Monday.
+    pub fn bucket_bounds(&self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            Grouping::Day => (date, date),
+            Grouping::Week => {
+                let start = date - Days::new(date.weekday().num_days_from_monday() as u64);
+                (start, start + Days::new(6))
+            }
+            Grouping::Month => {
+                let start = date.with_day(1).unwrap_or(date);
+                let end = start + Months::new(1) - Days::new(1);
+                (start, end)
+            }
+            Grouping::Year => {
+                let start = NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap_or(date);
+                let end = NaiveDate::from_ymd_opt(date.year(), 12, 31).unwrap_or(date);
+                (start, end)
+            }
+        }
+    }
+
+    pub fn label(&self, start: NaiveDate) -> String {
+        match self {
+            Grouping::Day => start.format("%Y-%m-%d").to_string(),
+            Grouping::Week => start.format("%G-W%V").to_string(), // e.g. "2025-W15"
+            Grouping::Month => start.format("%Y-%m").to_string(),
+            Grouping::Year => start.format("%Y").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct SpendingTimeSeriesFilter {
+    pub filter: Range,
+    pub group_by: Grouping,
+}
+
+/// `total` and `categories` are signed ledger sums, `expense` and `expenses` only count
+/// spending, as positive amounts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendingBucketModel {
+    pub period: String,
+    pub start: String,
+    pub end: String,
+    pub total: f32,
+    pub categories: BTreeMap<String, f32>,
+    pub expense: f32,
+    pub expenses: BTreeMap<String, f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct CompareSpendingModel {
+    pub current: Range,
+    /// Leave empty to compare against the equivalent previous period.
+    pub previous: Option<Range>,
+}
+
+/// Expenses are reported as positive spending, `net` is income minus expense.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendingPeriodModel {
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub expense: f32,
+    pub income: f32,
+    pub net: f32,
+}
+
+/// Expense amounts are reported as positive spending, `net` is income minus expense.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryBreakdownModel {
+    pub category: String,
+    pub expense: f32,
+    pub income: f32,
+    pub net: f32,
+    /// This category's expense as a percentage of all expenses in the range.
+    pub share_of_spending: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendingBreakdownModel {
+    pub income: f32,
+    pub expense: f32,
+    pub net: f32,
+    pub categories: Vec<CategoryBreakdownModel>,
+}
+
+/// `current` and `previous` are spending as positive amounts, so a positive delta means more
+/// spending. Income is kept apart and does not offset it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryComparisonModel {
+    pub category: String,
+    pub current: f32,
+    pub previous: f32,
+    pub delta: f32,
+    pub delta_percent: Option<f32>,
+    pub current_income: f32,
+    pub previous_income: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendingComparisonModel {
+    pub current: SpendingPeriodModel,
+    pub previous: SpendingPeriodModel,
+    pub categories: Vec<CategoryComparisonModel>,
+    pub biggest_movers: Vec<CategoryComparisonModel>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub enum MerchantRanking {
+    Frequency,
+    #[default]
+    Total,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TopSpendingFilter {
+    pub filter: Range,
+    /// How many entries to return, defaults to 10.
+    pub limit: Option<i64>,
+    #[serde(default)]
+    pub rank_merchants_by: MerchantRanking,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerchantSummaryModel {
+    pub description: String,
+    pub count: i64,
+    pub total: f32,
+    pub average: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopSpendingModel {
+    pub largest_transactions: Vec<SpendingScannerModel>,
+    pub top_merchants: Vec<MerchantSummaryModel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct SearchTransactionsModel {
+    /// Words to look for in the descriptions, e.g. "shoes" or "netflix".
+    pub query: String,
+    /// Defaults to lifetime.
+    pub filter: Option<Range>,
+    pub category: Option<String>,
+    /// How many matches to return, defaults to 10.
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionMatchModel {
+    pub id: i32,
+    pub amount: f32,
+    pub category: String,
+    pub description: String,
+    pub date: String,
+    pub snippet: String,
+    pub rank: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryStatisticsModel {
+    pub category: String,
+    pub count: usize,
+    pub total: f32,
+    pub mean: f32,
+    pub median: f32,
+    pub p90: f32,
+    pub min: f32,
+    pub max: f32,
+    pub std_dev: f32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct AddMonthlySpendingModel {
     pub title: String,
     pub amount: f32,
-    pub due_date: String, // DD
+    /// How the spending repeats, takes precedence over `due_date`.
+    pub recurrence: Option<RecurrenceModel>,
+    /// Shorthand for monthly on this day of the month (format: DD, or "last").
+    pub due_date: Option<String>,
+    /// Ledger category for posted occurrences (default: BILLS).
+    pub category: Option<String>,
+    #[serde(default)]
+    pub posting: PostingMode,
 }
 
 impl AddMonthlySpendingModel {
-    pub fn to_dto(&self) -> AddMonthlySpendingDto {
+    pub fn to_dto(&self, recurrence: &RecurrenceModel, today: NaiveDate) -> AddMonthlySpendingDto {
         AddMonthlySpendingDto {
             title: self.title.to_owned(),
             amount: self.amount,
-            due_date: self.due_date.to_owned(),
+            frequency: recurrence.frequency.as_str().to_string(),
+            interval: recurrence.interval as i32,
+            anchor_date: recurrence.anchor_date.to_owned(),
+            end_date: recurrence.end_date.to_owned(),
+            count: recurrence.count.map(|c| c as i32),
+            category: self
+                .category
+                .as_deref()
+                .unwrap_or(DEFAULT_BILL_CATEGORY)
+                .to_uppercase(),
+            posting: self.posting.as_str().to_string(),
+            post_from: today.to_string(),
         }
     }
 }
 
+/// Fields left empty keep their current value.
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct UpdateMonthlySpendingModel {
+    pub id: i32,
+    pub title: Option<String>,
+    pub amount: Option<f32>,
+    /// Replaces the schedule, takes precedence over `due_date`.
+    pub recurrence: Option<RecurrenceModel>,
+    /// Shorthand for monthly on this day of the month (format: DD, or "last").
+    pub due_date: Option<String>,
+    pub category: Option<String>,
+    pub posting: Option<PostingMode>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct RemoveMonthlySpendingModel {
     pub id: i32,
@@ -54,5 +318,15 @@ pub struct MonthlySpendingModel {
     pub id: i32,
     pub title: String,
     pub amount: f32,
-    pub due_date: String,
+    pub recurrence: RecurrenceModel,
+    pub category: String,
+    pub posting: PostingMode,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthlySpendingOccurrenceModel {
+    pub id: i32,
+    pub title: String,
+    pub amount: f32,
+    pub date: String,
 }
@@ -0,0 +1,92 @@
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::value_objects::{
+    recurrence::Frequency, spending_scanner::AddMonthlySpendingModel,
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct SubscriptionFilter {
+    /// Months of ledger history to scan, defaults to 24. Yearly charges need at least three
+    /// years of history to show up.
+    pub lookback_months: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuspectedSubscriptionModel {
+    /// Description of the latest charge.
+    pub description: String,
+    pub category: String,
+    /// e.g. "monthly" or "every 2 weeks".
+    pub cadence: String,
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub charges: usize,
+    pub median_interval_days: f32,
+    pub last_charge_date: String,
+    pub last_charge_amount: f32,
+    pub next_expected_date: String,
+    /// The latest charge amount times the charges in a year.
+    pub annual_cost: f32,
+    /// `false` when the next charge is overdue, so the subscription was probably cancelled.
+    pub active: bool,
+    /// Whether a monthly spending item already covers it.
+    pub tracked: bool,
+    /// Ready to pass to `add_monthly_spending`, only for subscriptions that are not tracked yet.
+    pub suggestion: Option<AddMonthlySpendingModel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionDetectionModel {
+    pub start: String,
+    pub end: String,
+    /// Annual cost of the active subscriptions that are not tracked yet.
+    pub untracked_annual_cost: f32,
+    pub subscriptions: Vec<SuspectedSubscriptionModel>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FixedCostSource {
+    /// Matches an item on the monthly spending list.
+    Scheduled,
+    /// Matches a subscription detected in the ledger.
+    Subscription,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixedCostModel {
+    /// Title of the scheduled item, or description of the subscription's latest charge.
+    pub name: String,
+    pub source: FixedCostSource,
+    pub total: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostCategoryModel {
+    pub category: String,
+    pub total: f32,
+    /// This category's spending as a percentage of the part it belongs to.
+    pub share_of_part: f32,
+}
+
+/// Spending is reported as positive amounts. Shares are percentages, `share_of_income` is
+/// `None` when there was no income in the range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostPartModel {
+    pub total: f32,
+    pub share_of_spending: f32,
+    pub share_of_income: Option<f32>,
+    pub categories: Vec<CostCategoryModel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixedVariableCostModel {
+    pub start: String,
+    pub end: String,
+    pub income: f32,
+    pub expense: f32,
+    pub fixed: CostPartModel,
+    pub variable: CostPartModel,
+    /// What the fixed spending was matched to, largest first.
+    pub fixed_costs: Vec<FixedCostModel>,
+}
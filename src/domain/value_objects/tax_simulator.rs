@@ -1,16 +1,168 @@
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
 use rmcp::schemars;
 use serde::{Deserialize, Serialize};
 
 use crate::domain::entities::tax_deductions_list::AddTaxDeductionsListDto;
 
+/// Assessable income types of section 40 of the Revenue Code, each with its own expense
+/// deduction. 40(5) is split by what is rented out because the rates differ.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, schemars::JsonSchema,
+)]
+pub enum IncomeType {
+    /// 40(1) Salary, wages and bonuses from employment.
+    #[default]
+    Salary,
+    /// 40(2) Fees and commissions from a job or service.
+    Service,
+    /// 40(3) Royalties from copyright and goodwill.
+    Royalty,
+    /// 40(4) Interest, dividends and other investment returns.
+    Investment,
+    /// 40(5) Rent from houses, buildings and other structures.
+    RentBuilding,
+    /// 40(5) Rent from agricultural land.
+    RentAgriculturalLand,
+    /// 40(5) Rent from any other land.
+    RentLand,
+    /// 40(5) Rent from vehicles.
+    RentVehicle,
+    /// 40(5) Rent from any other property.
+    RentOther,
+    /// 40(6) Medical practice.
+    Medical,
+    /// 40(6) Law, engineering, architecture, accounting and fine arts.
+    Professional,
+    /// 40(7) Contract work where the contractor supplies the materials.
+    Contracting,
+    /// 40(8) Business, commerce, agriculture and any other income.
+    Business,
+}
+
+impl IncomeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IncomeType::Salary => "Salary",
+            IncomeType::Service => "Service",
+            IncomeType::Royalty => "Royalty",
+            IncomeType::Investment => "Investment",
+            IncomeType::RentBuilding => "RentBuilding",
+            IncomeType::RentAgriculturalLand => "RentAgriculturalLand",
+            IncomeType::RentLand => "RentLand",
+            IncomeType::RentVehicle => "RentVehicle",
+            IncomeType::RentOther => "RentOther",
+            IncomeType::Medical => "Medical",
+            IncomeType::Professional => "Professional",
+            IncomeType::Contracting => "Contracting",
+            IncomeType::Business => "Business",
+        }
+    }
+
+    pub fn section(&self) -> &'static str {
+        match self {
+            IncomeType::Salary => "40(1)",
+            IncomeType::Service => "40(2)",
+            IncomeType::Royalty => "40(3)",
+            IncomeType::Investment => "40(4)",
+            IncomeType::RentBuilding
+            | IncomeType::RentAgriculturalLand
+            | IncomeType::RentLand
+            | IncomeType::RentVehicle
+            | IncomeType::RentOther => "40(5)",
+            IncomeType::Medical | IncomeType::Professional => "40(6)",
+            IncomeType::Contracting => "40(7)",
+            IncomeType::Business => "40(8)",
+        }
+    }
+}
+
+impl FromStr for IncomeType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "Salary" => Ok(IncomeType::Salary),
+            "Service" => Ok(IncomeType::Service),
+            "Royalty" => Ok(IncomeType::Royalty),
+            "Investment" => Ok(IncomeType::Investment),
+            "RentBuilding" => Ok(IncomeType::RentBuilding),
+            "RentAgriculturalLand" => Ok(IncomeType::RentAgriculturalLand),
+            "RentLand" => Ok(IncomeType::RentLand),
+            "RentVehicle" => Ok(IncomeType::RentVehicle),
+            "RentOther" => Ok(IncomeType::RentOther),
+            "Medical" => Ok(IncomeType::Medical),
+            "Professional" => Ok(IncomeType::Professional),
+            "Contracting" => Ok(IncomeType::Contracting),
+            "Business" => Ok(IncomeType::Business),
+            _ => Err(anyhow!("Unknown income type: {}", s)),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct TaxSimulateRequestModel {
     pub year: i32,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct IncomeByYearFilter {
+    pub year: i32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IncomeModel {
+    pub id: i32,
+    pub amount: f32,
+    pub category: String,
+    pub description: String,
+    pub date: String,
+    /// Untyped income counts as salary.
+    pub income_type: IncomeType,
+    pub section: String,
+}
+
+/// Expense deduction of income types sharing one rule, 40(1) and 40(2) share a single cap.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExpenseDeductionModel {
+    pub rule: String,
+    pub income_types: Vec<IncomeType>,
+    pub income: f32,
+    pub rate: f32,
+    pub cap: Option<f32>,
+    pub deduction: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TaxBracketModel {
+    pub lower: f32,
+    /// `None` for the top bracket.
+    pub upper: Option<f32>,
+    pub rate: f32,
+    /// Part of the net taxable income that falls in this bracket.
+    pub taxable: f32,
+    pub tax: f32,
+}
+
+/// Each stage of the calculation, in order: gross assessable income, minus the expense
+/// deduction, minus allowances and deductions, gives the net taxable income that the
+/// progressive brackets are applied to.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TaxSimulateResult {
+    pub year: i32,
+    pub gross_income: f32,
+    pub expense_deductions: Vec<ExpenseDeductionModel>,
+    pub expense_deduction: f32,
+    pub income_after_expenses: f32,
+    pub allowances: Vec<TaxDeductionsListModel>,
+    /// Sum of the allowances, limited to the income left after expenses.
+    pub total_allowances: f32,
+    pub net_taxable_income: f32,
+    pub brackets: Vec<TaxBracketModel>,
     pub must_pay: f32,
+    /// Tax as a percentage of gross income.
+    pub effective_rate: f32,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, schemars::JsonSchema)]
@@ -0,0 +1,53 @@
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::value_objects::spending_scanner::Range;
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct UnusualSpendingFilter {
+    pub filter: Range,
+    /// Months of history before the range used as the baseline, defaults to 6.
+    pub baseline_months: Option<u32>,
+    /// Robust z-score from which spending is flagged, defaults to 3.5.
+    pub threshold: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnusualTransactionModel {
+    pub id: i32,
+    pub date: String,
+    pub category: String,
+    pub description: String,
+    pub spent: f32,
+    pub baseline_median: f32,
+    pub score: f32,
+}
+
+/// What a category total in the range is compared with.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Baseline {
+    /// Totals of the same weekday in the history, used for a single day.
+    Weekday,
+    /// Monthly totals in the history, scaled to the number of days in the range.
+    Month,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnusualCategoryModel {
+    pub category: String,
+    pub spent: f32,
+    pub baseline: Baseline,
+    /// Days of the range compared, up to today.
+    pub period_days: i64,
+    pub baseline_mean: f32,
+    pub baseline_median: f32,
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnusualSpendingModel {
+    pub baseline_start: String,
+    pub baseline_end: String,
+    pub transactions: Vec<UnusualTransactionModel>,
+    pub categories: Vec<UnusualCategoryModel>,
+}
@@ -0,0 +1,332 @@
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+
+use crate::domain::value_objects::{
+    recurrence::{Frequency, RecurrenceModel},
+    spending_scanner::MonthlySpendingModel,
+};
+
+const PRODID: &str = "-//your-money-left-the-chat//Bills//EN";
+const UID_DOMAIN: &str = "your-money-left-the-chat";
+/// Content lines are folded at 75 octets (RFC 5545 section 3.1).
+const MAX_LINE_OCTETS: usize = 75;
+/// Every month has at least this many days, later anchor days need clamping.
+const SHORTEST_MONTH: u32 = 28;
+
+/// One all-day recurring event per scheduled payment. The UID is derived from the item id, so
+/// importing a fresh export updates the events instead of duplicating them.
+pub fn bills_calendar(
+    items: &[MonthlySpendingModel],
+    reminder_days: u32,
+    generated_at: DateTime<Utc>,
+) -> Result<String> {
+    let dtstamp = generated_at.format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "X-WR-CALNAME:Bills".to_string(),
+    ];
+
+    for item in items {
+        let anchor = item.recurrence.anchor()?;
+        let summary = format!("{} ({:.2})", item.title, item.amount);
+
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:monthly-spending-{}@{}", item.id, UID_DOMAIN),
+            format!("DTSTAMP:{}", dtstamp),
+            format!("DTSTART;VALUE=DATE:{}", ics_date(anchor)),
+            format!("DTEND;VALUE=DATE:{}", ics_date(anchor + Days::new(1))),
+            format!("RRULE:{}", rrule(&item.recurrence)?),
+            format!("SUMMARY:{}", escape_text(&summary)),
+            format!(
+                "DESCRIPTION:{}",
+                escape_text(&format!(
+                    "Amount: {:.2}\nCategory: {}",
+                    item.amount, item.category
+                ))
+            ),
+            format!("CATEGORIES:{}", escape_text(&item.category)),
+            "TRANSP:TRANSPARENT".to_string(),
+            "BEGIN:VALARM".to_string(),
+            "ACTION:DISPLAY".to_string(),
+            format!(
+                "DESCRIPTION:{}",
+                escape_text(&format!("{} is due", summary))
+            ),
+            format!("TRIGGER:{}", trigger(reminder_days)),
+            "END:VALARM".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    Ok(lines.iter().map(|line| fold(line)).collect())
+}
+
+/// Writes the calendar inside `export_dir` and returns where it ended up. `path` comes from the
+/// client, so it must be a relative `.ics` path that stays inside the directory, and only an
+/// earlier calendar is ever overwritten.
+pub fn write(export_dir: &Path, path: &str, calendar: &str) -> Result<PathBuf> {
+    let relative = Path::new(path);
+    let file_name = relative
+        .file_name()
+        .filter(|_| {
+            relative
+                .components()
+                .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "Export path must be a file inside the export directory, without .. : {}",
+                path
+            )
+        })?;
+    if !relative
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("ics"))
+    {
+        return Err(anyhow!("Export path must end in .ics: {}", path));
+    }
+
+    fs::create_dir_all(export_dir)?;
+    let root = fs::canonicalize(export_dir)?;
+
+    let parent = root.join(relative.parent().unwrap_or(Path::new("")));
+    fs::create_dir_all(&parent)?;
+    // A symlinked directory inside the export directory could still lead out of it
+    let parent = fs::canonicalize(parent)?;
+    if !parent.starts_with(&root) {
+        return Err(anyhow!("Export path leaves the export directory: {}", path));
+    }
+
+    let target = parent.join(file_name);
+    match fs::symlink_metadata(&target) {
+        Ok(metadata) if !metadata.is_file() => {
+            return Err(anyhow!(
+                "Refusing to overwrite {}, it is not a regular file",
+                target.display()
+            ));
+        }
+        Ok(_) if !fs::read(&target)?.starts_with(b"BEGIN:VCALENDAR") => {
+            return Err(anyhow!(
+                "Refusing to overwrite {}, it is not a calendar",
+                target.display()
+            ));
+        }
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+
+    fs::write(&target, calendar)
+        .map_err(|e| anyhow!("Failed to write calendar to {}: {}", target.display(), e))?;
+
+    Ok(target)
+}
+
+/// RFC 5545 skips months that do not have the anchor's day, while scheduled payments fall back
+/// to the last day of the month. Taking the last of the days from the 28th up to the anchor's
+/// day gives the same dates.
+fn rrule(recurrence: &RecurrenceModel) -> Result<String> {
+    let anchor = recurrence.anchor()?;
+
+    let mut parts = vec![
+        format!("FREQ={}", recurrence.frequency.as_str().to_uppercase()),
+        format!("INTERVAL={}", recurrence.interval),
+    ];
+
+    let clamped = matches!(recurrence.frequency, Frequency::Monthly | Frequency::Yearly)
+        && anchor.day() > SHORTEST_MONTH;
+    if clamped {
+        if recurrence.frequency == Frequency::Yearly {
+            parts.push(format!("BYMONTH={}", anchor.month()));
+        }
+        let days = (SHORTEST_MONTH..=anchor.day())
+            .map(|d| d.to_string())
+            .collect::<Vec<String>>();
+        parts.push(format!("BYMONTHDAY={}", days.join(",")));
+        parts.push("BYSETPOS=-1".to_string());
+    }
+
+    // COUNT and UNTIL must not both be given, only the one that ends the schedule first is kept
+    let end = recurrence.end()?;
+    let count_ends_first = match (recurrence.count, end) {
+        (Some(count), Some(end)) => recurrence
+            .nth(anchor, count.saturating_sub(1))
+            .is_some_and(|last| last <= end),
+        (count, _) => count.is_some(),
+    };
+    match (recurrence.count, end) {
+        (Some(count), _) if count_ends_first => parts.push(format!("COUNT={}", count)),
+        (_, Some(end)) => parts.push(format!("UNTIL={}", ics_date(end))),
+        _ => {}
+    }
+
+    Ok(parts.join(";"))
+}
+
+fn trigger(reminder_days: u32) -> String {
+    match reminder_days {
+        0 => "PT0S".to_string(),
+        days => format!("-P{}D", days),
+    }
+}
+
+fn ics_date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Splits a content line into CRLF terminated chunks of at most 75 octets, continuation lines
+/// start with a space. Never splits inside a UTF-8 character.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut octets = 0;
+
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded += "\r\n ";
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded += "\r\n";
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::{
+        domain::value_objects::{
+            bills::PostingMode,
+            recurrence::{Frequency, RecurrenceModel},
+            spending_scanner::MonthlySpendingModel,
+        },
+        infrastructure::calendar_exporter::{bills_calendar, rrule, write},
+    };
+
+    #[test]
+    fn test_bills_calendar_clamps_month_end_and_folds_lines() {
+        let items = vec![
+            MonthlySpendingModel {
+                id: 7,
+                title: "Rent, condo".to_string(),
+                amount: 12000.0,
+                recurrence: RecurrenceModel {
+                    frequency: Frequency::Monthly,
+                    interval: 1,
+                    anchor_date: "2025-01-30".to_string(),
+                    end_date: Some("2025-12-31".to_string()),
+                    count: None,
+                },
+                category: "BILLS".to_string(),
+                posting: PostingMode::Auto,
+            },
+            MonthlySpendingModel {
+                id: 8,
+                title: "ค่าไฟฟ้า การไฟฟ้านครหลวง ประจำเดือน".to_string(),
+                amount: 850.5,
+                recurrence: RecurrenceModel {
+                    frequency: Frequency::Weekly,
+                    interval: 2,
+                    anchor_date: "2025-01-05".to_string(),
+                    end_date: None,
+                    count: Some(10),
+                },
+                category: "UTILITIES".to_string(),
+                posting: PostingMode::ConfirmFirst,
+            },
+        ];
+        let generated_at = Utc.with_ymd_and_hms(2025, 1, 1, 8, 30, 0).unwrap();
+
+        let calendar = bills_calendar(&items, 1, generated_at).unwrap();
+
+        for line in calendar.split("\r\n") {
+            assert!(line.len() <= 75, "{} is longer than 75 octets", line);
+        }
+
+        // Folded lines continue after CRLF and a single space
+        let calendar = calendar.replace("\r\n ", "");
+
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert!(calendar.contains("UID:monthly-spending-7@your-money-left-the-chat\r\n"));
+        assert!(calendar.contains("DTSTAMP:20250101T083000Z\r\n"));
+        assert!(calendar.contains("DTSTART;VALUE=DATE:20250130\r\n"));
+        assert!(calendar.contains(
+            "RRULE:FREQ=MONTHLY;INTERVAL=1;BYMONTHDAY=28,29,30;BYSETPOS=-1;UNTIL=20251231\r\n"
+        ));
+        assert!(calendar.contains("RRULE:FREQ=WEEKLY;INTERVAL=2;COUNT=10\r\n"));
+        assert!(calendar.contains("SUMMARY:Rent\\, condo (12000.00)\r\n"));
+        assert!(calendar.contains("SUMMARY:ค่าไฟฟ้า การไฟฟ้านครหลวง ประจำเดือน (850.50)\r\n"));
+        assert!(calendar.contains("TRIGGER:-P1D\r\n"));
+        assert_eq!(calendar.matches("BEGIN:VALARM").count(), 2);
+    }
+
+    #[test]
+    fn test_rrule_keeps_only_the_first_of_count_and_until() {
+        let recurrence = |count: u32, end_date: &str| RecurrenceModel {
+            frequency: Frequency::Monthly,
+            interval: 1,
+            anchor_date: "2025-01-15".to_string(),
+            end_date: Some(end_date.to_string()),
+            count: Some(count),
+        };
+
+        // The third occurrence is on Mar 15, before the end date
+        assert_eq!(
+            rrule(&recurrence(3, "2025-12-31")).unwrap(),
+            "FREQ=MONTHLY;INTERVAL=1;COUNT=3"
+        );
+        assert_eq!(
+            rrule(&recurrence(12, "2025-03-31")).unwrap(),
+            "FREQ=MONTHLY;INTERVAL=1;UNTIL=20250331"
+        );
+    }
+
+    #[test]
+    fn test_write_stays_inside_the_export_directory() {
+        let export_dir =
+            std::env::temp_dir().join(format!("calendar-export-{}", std::process::id()));
+        let calendar = "BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n";
+
+        for path in [
+            "/tmp/bills.ics",
+            "../bills.ics",
+            "a/../../bills.ics",
+            "bills.txt",
+            "",
+        ] {
+            assert!(write(&export_dir, path, calendar).is_err(), "{}", path);
+        }
+
+        let written = write(&export_dir, "nested/bills.ics", calendar).unwrap();
+        assert!(written.ends_with("nested/bills.ics"));
+        // A fresh export replaces the earlier one
+        assert!(write(&export_dir, "nested/bills.ics", calendar).is_ok());
+
+        std::fs::write(export_dir.join("notes.ics"), "not a calendar").unwrap();
+        assert!(write(&export_dir, "notes.ics", calendar).is_err());
+
+        std::fs::remove_dir_all(&export_dir).unwrap();
+    }
+}
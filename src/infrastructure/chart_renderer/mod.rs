@@ -0,0 +1,280 @@
+pub mod svg;
+pub mod text;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock},
+};
+
+use anyhow::{Result, anyhow};
+use resvg::{tiny_skia, usvg};
+
+use crate::domain::value_objects::{
+    chart::ChartKind,
+    spending_scanner::{SpendingBreakdownModel, SpendingBucketModel},
+};
+
+/// Categories past this many are folded into a single "OTHER" slice, bar or line.
+const MAX_SERIES: usize = 8;
+const OTHER: &str = "OTHER";
+/// fontdb maps `sans-serif` to Arial, which many Linux boxes do not have.
+const SANS_SERIF_FAMILIES: &[&str] = &[
+    "Arial",
+    "Helvetica",
+    "DejaVu Sans",
+    "Liberation Sans",
+    "Noto Sans",
+];
+
+/// Charts spending only, a category with nothing but income has no slice or bar.
+pub fn category_chart(kind: ChartKind, title: &str, breakdown: &SpendingBreakdownModel) -> String {
+    let entries = top_entries(expense_entries(breakdown));
+
+    match kind {
+        ChartKind::Pie => svg::pie(title, &entries),
+        // One point per category is not much of a line, bars say the same thing
+        ChartKind::Bar | ChartKind::Line => svg::bar(title, &entries),
+    }
+}
+
+pub fn time_series_chart(kind: ChartKind, title: &str, buckets: &[SpendingBucketModel]) -> String {
+    match kind {
+        ChartKind::Pie => svg::pie(
+            title,
+            &top_entries(category_totals(buckets).into_iter().collect()),
+        ),
+        ChartKind::Bar => svg::bar(
+            title,
+            &buckets
+                .iter()
+                .map(|b| (b.period.to_owned(), b.expense))
+                .collect::<Vec<(String, f32)>>(),
+        ),
+        ChartKind::Line => {
+            let labels = buckets
+                .iter()
+                .map(|b| b.period.to_owned())
+                .collect::<Vec<String>>();
+
+            let totals = category_totals(buckets);
+            let shown = top_entries(totals.into_iter().collect())
+                .into_iter()
+                .map(|(category, _)| category)
+                .collect::<Vec<String>>();
+
+            let series = shown
+                .iter()
+                .map(|name| {
+                    let points = buckets
+                        .iter()
+                        .map(|b| match name.as_str() {
+                            OTHER => b
+                                .expenses
+                                .iter()
+                                .filter(|(c, _)| !shown.contains(c))
+                                .map(|(_, v)| v)
+                                .sum(),
+                            _ => b.expenses.get(name).copied().unwrap_or(0.0),
+                        })
+                        .collect::<Vec<f32>>();
+
+                    (name.to_owned(), points)
+                })
+                .collect::<Vec<(String, Vec<f32>)>>();
+
+            svg::line(title, &labels, &series)
+        }
+    }
+}
+
+pub fn to_png(svg: &str) -> Result<Vec<u8>> {
+    let options = usvg::Options {
+        fontdb: Arc::clone(fontdb()),
+        ..usvg::Options::default()
+    };
+
+    let tree = usvg::Tree::from_str(svg, &options)?;
+    let size = tree.size().to_int_size();
+
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| anyhow!("Invalid chart size: {:?}", size))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    Ok(pixmap.encode_png()?)
+}
+
+/// Loading system fonts takes a while, so it happens once per process.
+fn fontdb() -> &'static Arc<usvg::fontdb::Database> {
+    static FONTDB: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+
+    FONTDB.get_or_init(|| {
+        let mut fontdb = usvg::fontdb::Database::new();
+        fontdb.load_system_fonts();
+
+        let installed = SANS_SERIF_FAMILIES.iter().find(|family| {
+            fontdb
+                .faces()
+                .any(|face| face.families.iter().any(|(name, _)| name == *family))
+        });
+        if let Some(family) = installed {
+            fontdb.set_sans_serif_family(*family);
+        }
+
+        Arc::new(fontdb)
+    })
+}
+
+/// Categories with any spending and how much, as positive amounts.
+fn expense_entries(breakdown: &SpendingBreakdownModel) -> Vec<(String, f32)> {
+    breakdown
+        .categories
+        .iter()
+        .filter(|c| c.expense > 0.0)
+        .map(|c| (c.category.to_owned(), c.expense))
+        .collect()
+}
+
+/// Spending per category across all buckets.
+fn category_totals(buckets: &[SpendingBucketModel]) -> HashMap<String, f32> {
+    buckets.iter().flat_map(|b| b.expenses.iter()).fold(
+        HashMap::new(),
+        |mut acc, (category, amount)| {
+            *acc.entry(category.to_owned()).or_insert(0.0) += amount;
+            acc
+        },
+    )
+}
+
+/// Largest entries by absolute value first, with the tail summed into "OTHER".
+fn top_entries(mut entries: Vec<(String, f32)>) -> Vec<(String, f32)> {
+    entries.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()).then(a.0.cmp(&b.0)));
+
+    if entries.len() <= MAX_SERIES {
+        return entries;
+    }
+
+    let other = entries[MAX_SERIES - 1..]
+        .iter()
+        .map(|(_, v)| v)
+        .sum::<f32>();
+    entries.truncate(MAX_SERIES - 1);
+    entries.push((OTHER.to_string(), other));
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        domain::value_objects::{
+            chart::ChartKind,
+            spending_scanner::{
+                CategoryBreakdownModel, SpendingBreakdownModel, SpendingBucketModel,
+            },
+        },
+        infrastructure::chart_renderer::{category_chart, time_series_chart, to_png},
+    };
+
+    fn breakdown(categories: &[(&str, f32, f32)]) -> SpendingBreakdownModel {
+        SpendingBreakdownModel {
+            income: categories.iter().map(|(_, _, i)| i).sum(),
+            expense: categories.iter().map(|(_, e, _)| e).sum(),
+            net: categories.iter().map(|(_, e, i)| i - e).sum(),
+            categories: categories
+                .iter()
+                .map(|(category, expense, income)| CategoryBreakdownModel {
+                    category: category.to_string(),
+                    expense: *expense,
+                    income: *income,
+                    net: income - expense,
+                    share_of_spending: 0.0,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_category_chart_folds_small_categories() {
+        let categories = (1..=10)
+            .map(|i| (format!("C{}", i), i as f32 * 100.0, 0.0))
+            .collect::<Vec<(String, f32, f32)>>();
+        let categories = categories
+            .iter()
+            .map(|(c, e, i)| (c.as_str(), *e, *i))
+            .collect::<Vec<(&str, f32, f32)>>();
+
+        let svg = category_chart(ChartKind::Pie, "Spending", &breakdown(&categories));
+
+        assert_eq!(svg.matches("<path").count(), 8);
+        assert!(svg.contains("C10 1000.00"));
+        assert!(svg.contains("OTHER 600.00"));
+        assert!(!svg.contains("C1 "));
+    }
+
+    #[test]
+    fn test_charts_leave_out_income() {
+        let totals = breakdown(&[
+            ("SALARY", 0.0, 30000.0),
+            ("FOOD", 750.0, 0.0),
+            ("SHOPPING", 250.0, 100.0),
+        ]);
+
+        let pie = category_chart(ChartKind::Pie, "Spending", &totals);
+
+        assert_eq!(pie.matches("<path").count(), 2);
+        assert!(!pie.contains("SALARY"));
+        assert!(pie.contains("SHOPPING 250.00"));
+
+        let buckets = vec![SpendingBucketModel {
+            period: "2025-01".to_string(),
+            start: "2025-01-01".to_string(),
+            end: "2025-01-31".to_string(),
+            total: 29000.0,
+            categories: BTreeMap::from([
+                ("SALARY".to_string(), 30000.0),
+                ("FOOD".to_string(), -1000.0),
+            ]),
+            expense: 1000.0,
+            expenses: BTreeMap::from([("FOOD".to_string(), 1000.0)]),
+        }];
+
+        let bar = time_series_chart(ChartKind::Bar, "Spending", &buckets);
+        let line = time_series_chart(ChartKind::Line, "Spending", &buckets);
+
+        assert!(bar.contains("1000.00"));
+        assert!(!bar.contains("29000.00"));
+        assert!(!line.contains("SALARY"));
+    }
+
+    #[test]
+    fn test_time_series_chart_renders_png() {
+        let buckets = ["2025-01", "2025-02", "2025-03"]
+            .iter()
+            .enumerate()
+            .map(|(i, period)| SpendingBucketModel {
+                period: period.to_string(),
+                start: format!("{}-01", period),
+                end: format!("{}-28", period),
+                total: -(i as f32 + 1.0) * 100.0,
+                categories: BTreeMap::from([(
+                    "FOOD & DRINK".to_string(),
+                    -(i as f32 + 1.0) * 100.0,
+                )]),
+                expense: (i as f32 + 1.0) * 100.0,
+                expenses: BTreeMap::from([("FOOD & DRINK".to_string(), (i as f32 + 1.0) * 100.0)]),
+            })
+            .collect::<Vec<SpendingBucketModel>>();
+
+        let svg = time_series_chart(ChartKind::Line, "Food <monthly>", &buckets);
+
+        assert!(svg.contains("<polyline"));
+        assert!(svg.contains("Food &lt;monthly&gt;"));
+        assert!(svg.contains("FOOD &amp; DRINK"));
+
+        let png = to_png(&svg).unwrap();
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    }
+}
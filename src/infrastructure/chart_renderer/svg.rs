@@ -0,0 +1,193 @@
+use std::f32::consts::{PI, TAU};
+
+const WIDTH: f32 = 800.0;
+const HEIGHT: f32 = 480.0;
+const TITLE_HEIGHT: f32 = 60.0;
+const BAR_ROW_HEIGHT: f32 = 28.0;
+const MAX_X_LABELS: usize = 12;
+/// Rough width of one 12px glyph, used to keep x axis labels from running into each other.
+const LABEL_CHAR_WIDTH: f32 = 7.0;
+const PALETTE: &[&str] = &[
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+    "#9c755f", "#bab0ac",
+];
+
+/// Slices are sized by absolute value, the legend keeps the sign.
+pub fn pie(title: &str, slices: &[(String, f32)]) -> String {
+    let total = slices.iter().map(|(_, v)| v.abs()).sum::<f32>();
+    if total == 0.0 {
+        return no_data(title);
+    }
+
+    let (cx, cy, r) = (260.0, 260.0, 180.0);
+    let mut body = String::new();
+    let mut angle = -PI / 2.0;
+
+    for (i, (label, value)) in slices.iter().enumerate() {
+        let share = value.abs() / total;
+        let color = color(i);
+
+        if share >= 0.9999 {
+            body += &format!(r##"<circle cx="{cx}" cy="{cy}" r="{r}" fill="{color}"/>"##);
+        } else if share > 0.0 {
+            let end = angle + share * TAU;
+            let large_arc = if share > 0.5 { 1 } else { 0 };
+            body += &format!(
+                r##"<path d="M {cx} {cy} L {:.2} {:.2} A {r} {r} 0 {large_arc} 1 {:.2} {:.2} Z" fill="{color}" stroke="white" stroke-width="1"/>"##,
+                cx + r * angle.cos(),
+                cy + r * angle.sin(),
+                cx + r * end.cos(),
+                cy + r * end.sin(),
+            );
+            angle = end;
+        }
+
+        let ly = 90.0 + i as f32 * 26.0;
+        body += &format!(
+            r##"<rect x="500" y="{:.2}" width="14" height="14" fill="{color}"/><text x="522" y="{:.2}" font-size="13" fill="#333">{} {:.2} ({:.1}%)</text>"##,
+            ly - 11.0,
+            ly,
+            escape(label),
+            value,
+            share * 100.0
+        );
+    }
+
+    document(title, HEIGHT, &body)
+}
+
+/// Horizontal bars, one row per entry so long category names and many periods stay readable.
+pub fn bar(title: &str, bars: &[(String, f32)]) -> String {
+    let max = bars.iter().map(|(_, v)| v.abs()).fold(0.0, f32::max);
+    if max == 0.0 {
+        return no_data(title);
+    }
+
+    let (label_x, bar_x, bar_width) = (150.0, 160.0, WIDTH - 160.0 - 130.0);
+    let mut body = String::new();
+
+    for (i, (label, value)) in bars.iter().enumerate() {
+        let y = TITLE_HEIGHT + i as f32 * BAR_ROW_HEIGHT;
+        let width = value.abs() / max * bar_width;
+
+        body += &format!(
+            r##"<text x="{label_x}" y="{:.2}" font-size="13" text-anchor="end" fill="#333">{}</text><rect x="{bar_x}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}"/><text x="{:.2}" y="{:.2}" font-size="13" fill="#333">{:.2}</text>"##,
+            y + BAR_ROW_HEIGHT * 0.6,
+            escape(label),
+            y + BAR_ROW_HEIGHT * 0.15,
+            width,
+            BAR_ROW_HEIGHT * 0.7,
+            color(i),
+            bar_x + width + 6.0,
+            y + BAR_ROW_HEIGHT * 0.6,
+            value
+        );
+    }
+
+    let height = TITLE_HEIGHT + bars.len() as f32 * BAR_ROW_HEIGHT + 20.0;
+
+    document(title, height, &body)
+}
+
+pub fn line(title: &str, labels: &[String], series: &[(String, Vec<f32>)]) -> String {
+    let values = series.iter().flat_map(|(_, v)| v.iter().copied());
+    let min = values.clone().fold(0.0, f32::min);
+    let max = values.fold(0.0, f32::max);
+    if labels.is_empty() || max == min {
+        return no_data(title);
+    }
+
+    let (left, right, top, bottom) = (80.0, WIDTH - 170.0, TITLE_HEIGHT, HEIGHT - 60.0);
+    let x = |i: usize| match labels.len() {
+        1 => (left + right) / 2.0,
+        n => left + i as f32 * (right - left) / (n - 1) as f32,
+    };
+    let y = |v: f32| bottom - (v - min) / (max - min) * (bottom - top);
+
+    let mut body = String::new();
+
+    for tick in 0..=4 {
+        let value = min + (max - min) * tick as f32 / 4.0;
+        body += &format!(
+            r##"<line x1="{left}" y1="{0:.2}" x2="{right}" y2="{0:.2}" stroke="#e0e0e0"/><text x="{1}" y="{2:.2}" font-size="12" text-anchor="end" fill="#666">{3:.0}</text>"##,
+            y(value),
+            left - 8.0,
+            y(value) + 4.0,
+            value
+        );
+    }
+
+    let longest = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let fits = ((right - left) / (longest as f32 * LABEL_CHAR_WIDTH + 12.0)) as usize;
+    let step = labels.len().div_ceil(fits.clamp(1, MAX_X_LABELS));
+    for (i, label) in labels.iter().enumerate().step_by(step) {
+        body += &format!(
+            r##"<text x="{:.2}" y="{:.2}" font-size="12" text-anchor="middle" fill="#666">{}</text>"##,
+            x(i),
+            bottom + 20.0,
+            escape(label)
+        );
+    }
+
+    for (i, (name, points)) in series.iter().enumerate() {
+        let color = color(i);
+        let path = points
+            .iter()
+            .enumerate()
+            .map(|(p, v)| format!("{:.2},{:.2}", x(p), y(*v)))
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        body += &format!(
+            r##"<polyline points="{path}" fill="none" stroke="{color}" stroke-width="2"/>"##
+        );
+        if points.len() == 1 {
+            body += &format!(
+                r##"<circle cx="{:.2}" cy="{:.2}" r="4" fill="{color}"/>"##,
+                x(0),
+                y(points[0])
+            );
+        }
+
+        let ly = 90.0 + i as f32 * 24.0;
+        body += &format!(
+            r##"<rect x="{:.2}" y="{:.2}" width="14" height="3" fill="{color}"/><text x="{:.2}" y="{:.2}" font-size="13" fill="#333">{}</text>"##,
+            right + 20.0,
+            ly - 5.0,
+            right + 40.0,
+            ly,
+            escape(name)
+        );
+    }
+
+    document(title, HEIGHT, &body)
+}
+
+fn no_data(title: &str) -> String {
+    let body = format!(
+        r##"<text x="{}" y="{}" font-size="16" text-anchor="middle" fill="#999">No spending in this range</text>"##,
+        WIDTH / 2.0,
+        HEIGHT / 2.0
+    );
+
+    document(title, HEIGHT, &body)
+}
+
+fn document(title: &str, height: f32, body: &str) -> String {
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{height}" viewBox="0 0 {WIDTH} {height}" font-family="sans-serif"><rect width="100%" height="100%" fill="white"/><text x="{}" y="36" font-size="20" text-anchor="middle" fill="#333">{}</text>{body}</svg>"##,
+        WIDTH / 2.0,
+        escape(title)
+    )
+}
+
+fn color(i: usize) -> &'static str {
+    PALETTE[i % PALETTE.len()]
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
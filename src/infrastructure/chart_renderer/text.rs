@@ -0,0 +1,263 @@
+use crate::domain::value_objects::spending_scanner::{SpendingBreakdownModel, SpendingBucketModel};
+
+use super::{category_totals, expense_entries};
+
+const BAR_WIDTH: usize = 24;
+/// Partial blocks for the last cell of a bar, in eighths.
+const BAR_EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const TOTAL: &str = "TOTAL";
+
+/// One bar per category, largest first, with its spending and share of all spending.
+pub fn category_table(title: &str, breakdown: &SpendingBreakdownModel) -> String {
+    let mut entries = expense_entries(breakdown);
+    entries.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()).then(a.0.cmp(&b.0)));
+
+    bar_table(title, &entries)
+}
+
+/// Spending per category as bars, followed by the income, expense and net totals.
+pub fn breakdown_table(title: &str, breakdown: &SpendingBreakdownModel) -> String {
+    let mut table = bar_table(title, &expense_entries(breakdown));
+
+    let totals = [
+        ("INCOME", breakdown.income),
+        ("EXPENSE", breakdown.expense),
+        ("NET", breakdown.net),
+    ]
+    .map(|(label, value)| (label, format!("{:.2}", value)));
+    let amount_width = column_width(totals.iter().map(|(_, amount)| amount.as_str()));
+
+    table.push('\n');
+    for (label, amount) in totals {
+        table += &format!("{:<7}  {:>amount_width$}\n", label, amount);
+    }
+
+    table
+}
+
+/// Spending per period in date order as bars, then a sparkline per category across the same
+/// periods. Income is left out of both.
+pub fn time_series_table(title: &str, buckets: &[SpendingBucketModel]) -> String {
+    let periods = buckets
+        .iter()
+        .map(|b| (b.period.to_owned(), b.expense))
+        .collect::<Vec<(String, f32)>>();
+
+    let mut table = bar_table(title, &periods);
+    if periods.is_empty() {
+        return table;
+    }
+
+    let mut categories = category_totals(buckets)
+        .into_iter()
+        .collect::<Vec<(String, f32)>>();
+    categories.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()).then(a.0.cmp(&b.0)));
+
+    let rows = categories
+        .iter()
+        .map(|(category, total)| {
+            let points = buckets
+                .iter()
+                .map(|b| b.expenses.get(category).copied().unwrap_or(0.0))
+                .collect::<Vec<f32>>();
+
+            (
+                category.to_owned(),
+                sparkline(&points),
+                format!("{:.2}", total),
+            )
+        })
+        .collect::<Vec<(String, String, String)>>();
+
+    let grand_total = categories.iter().map(|(_, v)| v.abs()).sum::<f32>();
+    let label_width = column_width(rows.iter().map(|(c, _, _)| c.as_str()));
+    let amount_width = column_width(rows.iter().map(|(_, _, amount)| amount.as_str()));
+
+    table.push('\n');
+    for ((category, spark, amount), (_, total)) in rows.iter().zip(categories.iter()) {
+        table += &format!(
+            "{:<label_width$}  {}  {:>amount_width$}  {:>6}\n",
+            category,
+            spark,
+            amount,
+            share(*total, grand_total),
+        );
+    }
+
+    table
+}
+
+fn bar_table(title: &str, entries: &[(String, f32)]) -> String {
+    let mut table = format!("{}\n\n", title);
+
+    let max = entries.iter().map(|(_, v)| v.abs()).fold(0.0, f32::max);
+    if max == 0.0 {
+        table += "No spending in this range\n";
+        return table;
+    }
+
+    let total = entries.iter().map(|(_, v)| v).sum::<f32>();
+    let grand_total = entries.iter().map(|(_, v)| v.abs()).sum::<f32>();
+
+    let amounts = entries
+        .iter()
+        .map(|(_, v)| format!("{:.2}", v))
+        .collect::<Vec<String>>();
+    let total_amount = format!("{:.2}", total);
+
+    let label_width = column_width(entries.iter().map(|(l, _)| l.as_str()).chain([TOTAL]));
+    let amount_width = column_width(
+        amounts
+            .iter()
+            .map(String::as_str)
+            .chain([total_amount.as_str()]),
+    );
+
+    for ((label, value), amount) in entries.iter().zip(amounts.iter()) {
+        table += &format!(
+            "{:<label_width$}  {}  {:>amount_width$}  {:>6}\n",
+            label,
+            bar(value.abs() / max),
+            amount,
+            share(*value, grand_total),
+        );
+    }
+
+    table += &format!(
+        "{:<label_width$}  {:BAR_WIDTH$}  {:>amount_width$}\n",
+        TOTAL, "", total_amount,
+    );
+
+    table
+}
+
+/// `ratio` of 1.0 fills the whole bar, the last cell is drawn in eighths.
+fn bar(ratio: f32) -> String {
+    let eighths = (ratio.clamp(0.0, 1.0) * (BAR_WIDTH * 8) as f32).round() as usize;
+    let full = eighths / 8;
+
+    let mut bar = "█".repeat(full);
+    if full < BAR_WIDTH {
+        bar.push(BAR_EIGHTHS[eighths % 8]);
+        bar += &" ".repeat(BAR_WIDTH - full - 1);
+    }
+
+    bar
+}
+
+/// Scaled to the largest absolute value in the series, a blank means nothing was spent.
+fn sparkline(points: &[f32]) -> String {
+    let max = points.iter().map(|v| v.abs()).fold(0.0, f32::max);
+
+    points
+        .iter()
+        .map(|v| match *v == 0.0 || max == 0.0 {
+            true => ' ',
+            false => {
+                let level = (v.abs() / max * (SPARK_LEVELS.len() - 1) as f32).round() as usize;
+                SPARK_LEVELS[level]
+            }
+        })
+        .collect()
+}
+
+fn share(value: f32, grand_total: f32) -> String {
+    match grand_total == 0.0 {
+        true => "-".to_string(),
+        false => format!("{:.1}%", value.abs() / grand_total * 100.0),
+    }
+}
+
+fn column_width<'a>(cells: impl Iterator<Item = &'a str>) -> usize {
+    cells.map(|c| c.chars().count()).max().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        domain::value_objects::spending_scanner::{
+            CategoryBreakdownModel, SpendingBreakdownModel, SpendingBucketModel,
+        },
+        infrastructure::chart_renderer::text::{category_table, time_series_table},
+    };
+
+    fn category(category: &str, expense: f32, income: f32) -> CategoryBreakdownModel {
+        CategoryBreakdownModel {
+            category: category.to_string(),
+            expense,
+            income,
+            net: income - expense,
+            share_of_spending: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_category_table_aligns_amounts_and_shares() {
+        let breakdown = SpendingBreakdownModel {
+            income: 30000.0,
+            expense: 1000.0,
+            net: 29000.0,
+            categories: vec![
+                category("SALARY", 0.0, 30000.0),
+                category("FOOD", 750.0, 0.0),
+                category("TRANSPORT", 250.0, 0.0),
+            ],
+        };
+
+        let table = category_table("Spending", &breakdown);
+        let lines = table.lines().collect::<Vec<&str>>();
+
+        assert_eq!(lines[0], "Spending");
+        assert!(!table.contains("SALARY"));
+        assert!(lines[2].starts_with("FOOD       ████████████████████████"));
+        assert!(lines[2].ends_with("750.00   75.0%"));
+        assert!(lines[3].ends_with("250.00   25.0%"));
+        assert!(lines[4].starts_with("TOTAL"));
+        assert!(lines[4].ends_with("1000.00"));
+
+        let widths = lines[2..]
+            .iter()
+            .map(|l| l.find(".00").map(|i| l[..i].chars().count()))
+            .collect::<Vec<Option<usize>>>();
+
+        assert!(widths.windows(2).all(|w| w[0] == w[1]));
+    }
+
+    #[test]
+    fn test_time_series_table_leaves_out_income() {
+        let buckets = vec![SpendingBucketModel {
+            period: "2025-01".to_string(),
+            start: "2025-01-01".to_string(),
+            end: "2025-01-31".to_string(),
+            total: 29000.0,
+            categories: BTreeMap::from([
+                ("SALARY".to_string(), 30000.0),
+                ("FOOD".to_string(), -600.0),
+                ("TRANSPORT".to_string(), -400.0),
+            ]),
+            expense: 1000.0,
+            expenses: BTreeMap::from([
+                ("FOOD".to_string(), 600.0),
+                ("TRANSPORT".to_string(), 400.0),
+            ]),
+        }];
+
+        let table = time_series_table("Spending", &buckets);
+
+        assert!(!table.contains("SALARY"));
+        assert!(table.contains("2025-01"));
+        assert!(
+            table
+                .lines()
+                .any(|l| l.starts_with("FOOD") && l.ends_with("60.0%"))
+        );
+        assert!(
+            table
+                .lines()
+                .any(|l| l.starts_with("TOTAL") && l.ends_with("1000.00"))
+        );
+    }
+}
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_my_ledger_category_date;
+
+DROP INDEX IF EXISTS idx_my_ledger_date;
@@ -0,0 +1,4 @@
+-- category and amount ride along so date range aggregates never have to touch the table
+CREATE INDEX IF NOT EXISTS idx_my_ledger_date ON my_ledger (date, category, amount);
+
+CREATE INDEX IF NOT EXISTS idx_my_ledger_category_date ON my_ledger (category, date);
@@ -0,0 +1,7 @@
+DROP TRIGGER IF EXISTS my_ledger_fts_after_update;
+
+DROP TRIGGER IF EXISTS my_ledger_fts_after_delete;
+
+DROP TRIGGER IF EXISTS my_ledger_fts_after_insert;
+
+DROP TABLE IF EXISTS my_ledger_fts;
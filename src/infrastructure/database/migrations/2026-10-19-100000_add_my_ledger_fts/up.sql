@@ -0,0 +1,22 @@
+-- External content table: the text lives in my_ledger, the triggers keep the index in sync
+CREATE VIRTUAL TABLE IF NOT EXISTS my_ledger_fts USING fts5 (
+    description,
+    content = 'my_ledger',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO my_ledger_fts (my_ledger_fts) VALUES ('rebuild');
+
+CREATE TRIGGER IF NOT EXISTS my_ledger_fts_after_insert AFTER INSERT ON my_ledger BEGIN
+    INSERT INTO my_ledger_fts (rowid, description) VALUES (new.id, new.description);
+END;
+
+CREATE TRIGGER IF NOT EXISTS my_ledger_fts_after_delete AFTER DELETE ON my_ledger BEGIN
+    INSERT INTO my_ledger_fts (my_ledger_fts, rowid, description) VALUES ('delete', old.id, old.description);
+END;
+
+CREATE TRIGGER IF NOT EXISTS my_ledger_fts_after_update AFTER UPDATE OF description ON my_ledger BEGIN
+    INSERT INTO my_ledger_fts (my_ledger_fts, rowid, description) VALUES ('delete', old.id, old.description);
+    INSERT INTO my_ledger_fts (rowid, description) VALUES (new.id, new.description);
+END;
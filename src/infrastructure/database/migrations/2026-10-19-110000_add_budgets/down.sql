@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_budgets_category;
+DROP TABLE IF EXISTS budgets;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS budgets (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    category TEXT NOT NULL,
+    monthly_limit REAL NOT NULL,
+    -- Inclusive YYYY-MM bounds, NULL means open ended
+    effective_from TEXT,
+    effective_to TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_budgets_category ON budgets (category);
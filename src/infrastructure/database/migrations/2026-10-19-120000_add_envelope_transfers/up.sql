@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- Money moving into, out of or between envelopes. A NULL side is the unassigned pool.
+CREATE TABLE IF NOT EXISTS envelope_transfers (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    from_envelope TEXT,
+    to_envelope TEXT,
+    amount REAL NOT NULL CHECK (amount > 0),
+    date TEXT NOT NULL,
+    CHECK (from_envelope IS NOT NULL OR to_envelope IS NOT NULL)
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE budgets DROP COLUMN rollover_cap;
+ALTER TABLE budgets DROP COLUMN rollover;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- One of 'None', 'CarrySurplus' or 'CarryBoth'
+ALTER TABLE budgets ADD COLUMN rollover TEXT NOT NULL DEFAULT 'None';
+-- Largest amount carried into the next month either way, NULL means no cap
+ALTER TABLE budgets ADD COLUMN rollover_cap REAL;
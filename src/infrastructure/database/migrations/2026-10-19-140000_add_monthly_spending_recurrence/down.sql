@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE monthly_spending ADD COLUMN due_date TEXT NOT NULL DEFAULT '01';
+
+UPDATE monthly_spending SET due_date = strftime('%d', anchor_date);
+
+ALTER TABLE monthly_spending DROP COLUMN count;
+ALTER TABLE monthly_spending DROP COLUMN end_date;
+ALTER TABLE monthly_spending DROP COLUMN anchor_date;
+ALTER TABLE monthly_spending DROP COLUMN interval;
+ALTER TABLE monthly_spending DROP COLUMN frequency;
@@ -0,0 +1,35 @@
+-- Your SQL goes here
+-- due_date was a bare day of month, or a full date. Anything else cannot be anchored without
+-- guessing, so the migration stops with "CHECK constraint failed: unparsable_legacy_due_date"
+-- until those rows are fixed by hand.
+CREATE TEMP TABLE legacy_due_date_check (
+    id INTEGER,
+    due_date TEXT,
+    CONSTRAINT unparsable_legacy_due_date CHECK (0)
+);
+
+INSERT INTO legacy_due_date_check
+SELECT id, due_date FROM monthly_spending
+WHERE date(due_date) IS NOT due_date
+    AND NOT (
+        (due_date GLOB '[0-9]' OR due_date GLOB '[0-9][0-9]')
+        AND CAST(due_date AS INTEGER) BETWEEN 1 AND 31
+    );
+
+DROP TABLE legacy_due_date_check;
+
+ALTER TABLE monthly_spending ADD COLUMN frequency TEXT NOT NULL DEFAULT 'Monthly';
+ALTER TABLE monthly_spending ADD COLUMN interval INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE monthly_spending ADD COLUMN anchor_date TEXT NOT NULL DEFAULT '2025-01-01';
+ALTER TABLE monthly_spending ADD COLUMN end_date TEXT;
+ALTER TABLE monthly_spending ADD COLUMN count INTEGER;
+
+-- due_date was a bare day of month. January has every day from 1 to 31, so anchoring there keeps
+-- the day, and later months fall back to their last day when they are shorter.
+UPDATE monthly_spending
+SET anchor_date = CASE
+    WHEN date(due_date) IS due_date THEN due_date
+    ELSE printf('2025-01-%02d', CAST(due_date AS INTEGER))
+END;
+
+ALTER TABLE monthly_spending DROP COLUMN due_date;
@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_bill_occurrences_ledger_id;
+DROP TABLE IF EXISTS bill_occurrences;
+
+ALTER TABLE monthly_spending DROP COLUMN post_from;
+ALTER TABLE monthly_spending DROP COLUMN posting;
+ALTER TABLE monthly_spending DROP COLUMN category;
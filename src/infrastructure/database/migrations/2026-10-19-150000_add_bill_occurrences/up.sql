@@ -0,0 +1,18 @@
+-- Your SQL goes here
+ALTER TABLE monthly_spending ADD COLUMN category TEXT NOT NULL DEFAULT 'BILLS';
+-- 'Auto' posts due occurrences into my_ledger, 'ConfirmFirst' waits for a confirmation
+ALTER TABLE monthly_spending ADD COLUMN posting TEXT NOT NULL DEFAULT 'ConfirmFirst';
+-- Occurrences before this date are never posted, so existing items do not backfill their history
+ALTER TABLE monthly_spending ADD COLUMN post_from TEXT NOT NULL DEFAULT '0001-01-01';
+UPDATE monthly_spending SET post_from = date('now');
+
+CREATE TABLE IF NOT EXISTS bill_occurrences (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    monthly_spending_id INTEGER NOT NULL REFERENCES monthly_spending (id) ON DELETE CASCADE,
+    due_date TEXT NOT NULL,
+    ledger_id INTEGER REFERENCES my_ledger (id) ON DELETE SET NULL,
+    UNIQUE (monthly_spending_id, due_date)
+);
+
+-- Lets a my_ledger delete find the occurrences to unlink without scanning the table
+CREATE INDEX IF NOT EXISTS idx_bill_occurrences_ledger_id ON bill_occurrences (ledger_id);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE bill_occurrences DROP COLUMN paid_date;
+ALTER TABLE bill_occurrences DROP COLUMN status;
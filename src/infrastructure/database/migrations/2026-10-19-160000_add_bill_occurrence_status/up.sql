@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE bill_occurrences ADD COLUMN status TEXT NOT NULL DEFAULT 'Pending';
+ALTER TABLE bill_occurrences ADD COLUMN paid_date TEXT;
+
+UPDATE bill_occurrences SET status = 'Paid', paid_date = due_date WHERE ledger_id IS NOT NULL;
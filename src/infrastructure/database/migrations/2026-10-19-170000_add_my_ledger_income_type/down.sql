@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE my_ledger DROP COLUMN income_type;
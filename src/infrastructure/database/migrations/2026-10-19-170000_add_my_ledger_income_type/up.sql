@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- Section 40 income type of positive entries, NULL for expenses and untyped income
+ALTER TABLE my_ledger ADD COLUMN income_type TEXT;
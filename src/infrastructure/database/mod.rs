@@ -2,11 +2,14 @@ pub mod repositories;
 pub mod schema;
 
 use anyhow::Result;
+use chrono::{Days, NaiveDate};
 use diesel::{
     prelude::*,
     r2d2::{ConnectionManager, Pool},
 };
 
+use crate::domain::value_objects::spending_scanner::Grouping;
+
 pub type SqlitePoolSquad = Pool<ConnectionManager<SqliteConnection>>;
 
 pub fn conn(database_url: &str) -> Result<SqlitePoolSquad> {
@@ -14,3 +17,29 @@ pub fn conn(database_url: &str) -> Result<SqlitePoolSquad> {
     let pool = Pool::builder().build(manager)?;
     Ok(pool)
 }
+
+/// Half-open `[start, end)` bounds of the day, month or year containing `date`. Comparing the
+/// text `date` column against these keeps the filter sargable, unlike a `LIKE 'YYYY-MM%'` prefix.
+pub fn date_bounds(grouping: Grouping, date: NaiveDate) -> (String, String) {
+    let (start, end) = grouping.bucket_bounds(date);
+    (start.to_string(), (end + Days::new(1)).to_string())
+}
+
+#[cfg(test)]
+pub fn test_pool() -> SqlitePoolSquad {
+    use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+
+    const MIGRATIONS: EmbeddedMigrations =
+        embed_migrations!("src/infrastructure/database/migrations");
+
+    // Every in-memory connection is its own database, so the pool must hold exactly one.
+    let manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+    let pool = Pool::builder().max_size(1).build(manager).unwrap();
+
+    pool.get()
+        .unwrap()
+        .run_pending_migrations(MIGRATIONS)
+        .unwrap();
+
+    pool
+}
@@ -0,0 +1,322 @@
+use anyhow::{Result, anyhow};
+use diesel::{dsl, prelude::*};
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    domain::{
+        entities::{
+            bill_occurrences::{AddBillOccurrenceDto, BillOccurrence, SettleBillOccurrenceDto},
+            my_ledger::RecordMyLedgerDto,
+        },
+        repositories::bills::BillRepository,
+        value_objects::bills::BillStatus,
+    },
+    infrastructure::database::{
+        SqlitePoolSquad,
+        schema::{bill_occurrences, my_ledger},
+    },
+};
+
+#[derive(Clone)]
+pub struct BillSqlite {
+    db_pool: Arc<SqlitePoolSquad>,
+}
+
+impl BillSqlite {
+    pub fn new(db_pool: Arc<SqlitePoolSquad>) -> Self {
+        Self { db_pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl BillRepository for BillSqlite {
+    async fn add_occurrences(
+        &self,
+        add_bill_occurrence_dtos: Vec<AddBillOccurrenceDto>,
+    ) -> Result<()> {
+        if add_bill_occurrence_dtos.is_empty() {
+            return Ok(());
+        }
+
+        let conn = &mut self.db_pool.get()?;
+
+        // UNIQUE (monthly_spending_id, due_date) keeps every occurrence to a single row
+        diesel::insert_or_ignore_into(bill_occurrences::table)
+            .values(&add_bill_occurrence_dtos)
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    async fn latest_due_dates(&self) -> Result<HashMap<i32, String>> {
+        let conn = &mut self.db_pool.get()?;
+
+        let results = bill_occurrences::table
+            .group_by(bill_occurrences::monthly_spending_id)
+            .select((
+                bill_occurrences::monthly_spending_id,
+                dsl::max(bill_occurrences::due_date),
+            ))
+            .load::<(i32, Option<String>)>(conn)?;
+
+        Ok(results
+            .into_iter()
+            .filter_map(|(id, due_date)| Some((id, due_date?)))
+            .collect())
+    }
+
+    async fn find_occurrence(
+        &self,
+        monthly_spending_id: i32,
+        due_date: String,
+    ) -> Result<Option<BillOccurrence>> {
+        let conn = &mut self.db_pool.get()?;
+
+        let result = bill_occurrences::table
+            .filter(bill_occurrences::monthly_spending_id.eq(monthly_spending_id))
+            .filter(bill_occurrences::due_date.eq(due_date))
+            .select(BillOccurrence::as_select())
+            .first::<BillOccurrence>(conn)
+            .optional()?;
+
+        Ok(result)
+    }
+
+    async fn view_pending_occurrences(&self, until: String) -> Result<Vec<BillOccurrence>> {
+        let conn = &mut self.db_pool.get()?;
+
+        let results = bill_occurrences::table
+            .filter(bill_occurrences::status.eq(BillStatus::Pending.as_str()))
+            .filter(bill_occurrences::due_date.le(until))
+            .order((bill_occurrences::due_date.asc(), bill_occurrences::id.asc()))
+            .select(BillOccurrence::as_select())
+            .load::<BillOccurrence>(conn)?;
+
+        Ok(results)
+    }
+
+    async fn post_occurrence(
+        &self,
+        occurrence_id: i32,
+        record_my_ledger_dto: RecordMyLedgerDto,
+    ) -> Result<Option<i32>> {
+        let conn = &mut self.db_pool.get()?;
+
+        conn.immediate_transaction(|conn| {
+            if !is_pending(conn, occurrence_id)? {
+                return Ok(None);
+            }
+
+            let ledger_id = diesel::insert_into(my_ledger::table)
+                .values(&record_my_ledger_dto)
+                .returning(my_ledger::id)
+                .get_result::<i32>(conn)?;
+
+            diesel::update(bill_occurrences::table.filter(bill_occurrences::id.eq(occurrence_id)))
+                .set(&SettleBillOccurrenceDto {
+                    status: BillStatus::Paid.as_str().to_string(),
+                    paid_date: Some(record_my_ledger_dto.date.to_owned()),
+                    ledger_id: Some(ledger_id),
+                })
+                .execute(conn)?;
+
+            Ok(Some(ledger_id))
+        })
+    }
+
+    async fn settle_occurrence(
+        &self,
+        occurrence_id: i32,
+        settle_bill_occurrence_dto: SettleBillOccurrenceDto,
+    ) -> Result<bool> {
+        let conn = &mut self.db_pool.get()?;
+
+        conn.immediate_transaction(|conn| {
+            if let Some(ledger_id) = settle_bill_occurrence_dto.ledger_id {
+                let exists = diesel::select(diesel::dsl::exists(
+                    my_ledger::table.filter(my_ledger::id.eq(ledger_id)),
+                ))
+                .get_result::<bool>(conn)?;
+                if !exists {
+                    return Err(anyhow!("No ledger transaction with id {}", ledger_id));
+                }
+            }
+
+            if !is_pending(conn, occurrence_id)? {
+                return Ok(false);
+            }
+
+            diesel::update(bill_occurrences::table.filter(bill_occurrences::id.eq(occurrence_id)))
+                .set(&settle_bill_occurrence_dto)
+                .execute(conn)?;
+
+            Ok(true)
+        })
+    }
+}
+
+fn is_pending(conn: &mut SqliteConnection, occurrence_id: i32) -> QueryResult<bool> {
+    diesel::select(diesel::dsl::exists(
+        bill_occurrences::table
+            .filter(bill_occurrences::id.eq(occurrence_id))
+            .filter(bill_occurrences::status.eq(BillStatus::Pending.as_str())),
+    ))
+    .get_result::<bool>(conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use diesel::{prelude::*, sql_types::Text};
+
+    use crate::{
+        domain::{
+            entities::{
+                bill_occurrences::{AddBillOccurrenceDto, SettleBillOccurrenceDto},
+                monthly_spending::AddMonthlySpendingDto,
+                my_ledger::RecordMyLedgerDto,
+            },
+            repositories::bills::BillRepository,
+            value_objects::bills::BillStatus,
+        },
+        infrastructure::database::{
+            repositories::bills::BillSqlite,
+            schema::{monthly_spending, my_ledger},
+            test_pool,
+        },
+    };
+
+    #[derive(QueryableByName)]
+    struct QueryPlan {
+        #[diesel(sql_type = Text)]
+        detail: String,
+    }
+
+    #[test]
+    fn test_ledger_link_uses_index() {
+        let conn = &mut test_pool().get().unwrap();
+
+        // The lookup SQLite runs to apply ON DELETE SET NULL when a ledger entry goes away
+        let plan = diesel::sql_query(
+            "EXPLAIN QUERY PLAN SELECT id FROM bill_occurrences WHERE ledger_id = 1",
+        )
+        .load::<QueryPlan>(conn)
+        .unwrap()
+        .into_iter()
+        .map(|p| p.detail)
+        .collect::<Vec<String>>();
+
+        assert!(
+            plan.iter()
+                .any(|detail| detail.contains("idx_bill_occurrences_ledger_id")),
+            "{:?}",
+            plan
+        );
+    }
+
+    #[tokio::test]
+    async fn test_post_occurrence_is_idempotent() {
+        let db_pool = Arc::new(test_pool());
+
+        let monthly_spending_id = {
+            let conn = &mut db_pool.get().unwrap();
+
+            diesel::insert_into(monthly_spending::table)
+                .values(&AddMonthlySpendingDto {
+                    title: "Rent".to_string(),
+                    amount: 12000.0,
+                    frequency: "Monthly".to_string(),
+                    interval: 1,
+                    anchor_date: "2025-01-31".to_string(),
+                    end_date: None,
+                    count: None,
+                    category: "BILLS".to_string(),
+                    posting: "Auto".to_string(),
+                    post_from: "2025-01-01".to_string(),
+                })
+                .returning(monthly_spending::id)
+                .get_result::<i32>(conn)
+                .unwrap()
+        };
+
+        let bill_repository = BillSqlite::new(Arc::clone(&db_pool));
+        let occurrence = |due_date: &str| AddBillOccurrenceDto {
+            monthly_spending_id,
+            due_date: due_date.to_string(),
+        };
+
+        for _ in 0..2 {
+            bill_repository
+                .add_occurrences(vec![occurrence("2025-01-31"), occurrence("2025-02-28")])
+                .await
+                .unwrap();
+        }
+
+        let unposted = bill_repository
+            .view_pending_occurrences("2025-12-31".to_string())
+            .await
+            .unwrap();
+        assert_eq!(unposted.len(), 2);
+
+        let latest = bill_repository.latest_due_dates().await.unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[&monthly_spending_id], "2025-02-28");
+
+        let rent = RecordMyLedgerDto {
+            amount: -12000.0,
+            category: "BILLS".to_string(),
+            description: "Rent".to_string(),
+            date: "2025-01-31".to_string(),
+            income_type: None,
+        };
+        let first = bill_repository
+            .post_occurrence(unposted[0].id, rent.clone())
+            .await
+            .unwrap();
+        let second = bill_repository
+            .post_occurrence(unposted[0].id, rent)
+            .await
+            .unwrap();
+
+        assert!(first.is_some());
+        assert_eq!(second, None);
+
+        let posted = bill_repository
+            .find_occurrence(monthly_spending_id, "2025-01-31".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(posted.status(), BillStatus::Paid);
+        assert_eq!(posted.paid_date.as_deref(), Some("2025-01-31"));
+        assert_eq!(posted.ledger_id, first);
+
+        let unposted = bill_repository
+            .view_pending_occurrences("2025-12-31".to_string())
+            .await
+            .unwrap();
+        assert_eq!(unposted.len(), 1);
+        assert_eq!(unposted[0].due_date, "2025-02-28");
+
+        let skip = SettleBillOccurrenceDto {
+            status: BillStatus::Skipped.as_str().to_string(),
+            paid_date: None,
+            ledger_id: None,
+        };
+        let skipped = bill_repository
+            .settle_occurrence(unposted[0].id, skip.clone())
+            .await
+            .unwrap();
+        let skipped_again = bill_repository
+            .settle_occurrence(unposted[0].id, skip)
+            .await
+            .unwrap();
+
+        assert!(skipped);
+        assert!(!skipped_again);
+
+        let conn = &mut db_pool.get().unwrap();
+        let ledger_rows = my_ledger::table.count().get_result::<i64>(conn).unwrap();
+        assert_eq!(ledger_rows, 1);
+    }
+}
@@ -0,0 +1,193 @@
+use anyhow::Result;
+use diesel::{prelude::*, sql_types::Text};
+use std::sync::Arc;
+
+use crate::{
+    domain::{
+        entities::budgets::{Budget, MonthlySpent, SetBudgetDto},
+        repositories::budget::BudgetRepository,
+    },
+    infrastructure::database::{SqlitePoolSquad, schema::budgets},
+};
+
+#[derive(Clone)]
+pub struct BudgetSqlite {
+    db_pool: Arc<SqlitePoolSquad>,
+}
+
+impl BudgetSqlite {
+    pub fn new(db_pool: Arc<SqlitePoolSquad>) -> Self {
+        Self { db_pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl BudgetRepository for BudgetSqlite {
+    async fn view_all_budgets(&self) -> Result<Vec<Budget>> {
+        let conn = &mut self.db_pool.get()?;
+
+        let results = budgets::table
+            .order((budgets::category.asc(), budgets::id.asc()))
+            .select(Budget::as_select())
+            .load::<Budget>(conn)?;
+
+        Ok(results)
+    }
+
+    async fn set_budget(&self, set_budget_dto: SetBudgetDto) -> Result<i32> {
+        let conn = &mut self.db_pool.get()?;
+
+        conn.transaction(|conn| {
+            // `=` never matches NULL, so open ended bounds need `IS NULL` to find the same period
+            let mut existing = budgets::table
+                .filter(budgets::category.eq(&set_budget_dto.category))
+                .select(budgets::id)
+                .into_boxed();
+            existing = match &set_budget_dto.effective_from {
+                Some(from) => existing.filter(budgets::effective_from.eq(from)),
+                None => existing.filter(budgets::effective_from.is_null()),
+            };
+            existing = match &set_budget_dto.effective_to {
+                Some(to) => existing.filter(budgets::effective_to.eq(to)),
+                None => existing.filter(budgets::effective_to.is_null()),
+            };
+
+            match existing.first::<i32>(conn).optional()? {
+                Some(id) => {
+                    diesel::update(budgets::table.filter(budgets::id.eq(id)))
+                        .set(&set_budget_dto)
+                        .execute(conn)?;
+
+                    Ok(id)
+                }
+                None => diesel::insert_into(budgets::table)
+                    .values(&set_budget_dto)
+                    .returning(budgets::id)
+                    .get_result::<i32>(conn),
+            }
+        })
+        .map_err(Into::into)
+    }
+
+    async fn monthly_spent(&self, before: String) -> Result<Vec<MonthlySpent>> {
+        let conn = &mut self.db_pool.get()?;
+
+        let results = diesel::sql_query(
+            "SELECT category, substr(date, 1, 7) AS month, -SUM(amount) AS spent \
+            FROM my_ledger \
+            WHERE amount < 0 \
+                AND date < ? \
+                AND category IN (SELECT category FROM budgets) \
+            GROUP BY category, month \
+            ORDER BY category, month",
+        )
+        .bind::<Text, _>(before)
+        .load::<MonthlySpent>(conn)?;
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use diesel::prelude::*;
+
+    use crate::{
+        domain::{
+            entities::{budgets::SetBudgetDto, my_ledger::RecordMyLedgerDto},
+            repositories::budget::BudgetRepository,
+        },
+        infrastructure::database::{
+            repositories::budget::BudgetSqlite, schema::my_ledger, test_pool,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_set_budget_replaces_same_period() {
+        let budget_repository = BudgetSqlite::new(Arc::new(test_pool()));
+        let set_budget = |monthly_limit: f32, effective_from: Option<&str>| SetBudgetDto {
+            category: "FOOD".to_string(),
+            monthly_limit,
+            effective_from: effective_from.map(str::to_string),
+            effective_to: None,
+            rollover: "None".to_string(),
+            rollover_cap: None,
+        };
+
+        let open_ended = budget_repository
+            .set_budget(set_budget(4000.0, None))
+            .await
+            .unwrap();
+        let replaced = budget_repository
+            .set_budget(set_budget(4500.0, None))
+            .await
+            .unwrap();
+        let from_april = budget_repository
+            .set_budget(set_budget(6000.0, Some("2025-04")))
+            .await
+            .unwrap();
+
+        assert_eq!(open_ended, replaced);
+        assert_ne!(open_ended, from_april);
+
+        let budgets = budget_repository.view_all_budgets().await.unwrap();
+
+        assert_eq!(budgets.len(), 2);
+        assert_eq!(budgets[0].monthly_limit, 4500.0);
+        assert_eq!(budgets[1].effective_from.as_deref(), Some("2025-04"));
+    }
+
+    #[tokio::test]
+    async fn test_monthly_spent_covers_budgeted_categories() {
+        let db_pool = Arc::new(test_pool());
+
+        {
+            let conn = &mut db_pool.get().unwrap();
+
+            let entries = [
+                (-100.0, "FOOD", "2025-03-02"),
+                (-50.0, "FOOD", "2025-03-30"),
+                (200.0, "FOOD", "2025-03-31"),
+                (-70.0, "FOOD", "2025-04-01"),
+                (-999.0, "TRAVEL", "2025-03-05"),
+            ]
+            .map(|(amount, category, date)| RecordMyLedgerDto {
+                amount,
+                category: category.to_string(),
+                description: "Test".to_string(),
+                date: date.to_string(),
+                income_type: None,
+            });
+
+            diesel::insert_into(my_ledger::table)
+                .values(&entries[..])
+                .execute(conn)
+                .unwrap();
+        }
+
+        let budget_repository = BudgetSqlite::new(Arc::clone(&db_pool));
+        budget_repository
+            .set_budget(SetBudgetDto {
+                category: "FOOD".to_string(),
+                monthly_limit: 1000.0,
+                effective_from: None,
+                effective_to: None,
+                rollover: "CarryBoth".to_string(),
+                rollover_cap: None,
+            })
+            .await
+            .unwrap();
+
+        let monthly_spent = budget_repository
+            .monthly_spent("2025-04-01".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(monthly_spent.len(), 1);
+        assert_eq!(monthly_spent[0].category, "FOOD");
+        assert_eq!(monthly_spent[0].month, "2025-03");
+        assert_eq!(monthly_spent[0].spent, 150.0);
+    }
+}
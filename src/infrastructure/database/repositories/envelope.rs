@@ -0,0 +1,201 @@
+use anyhow::Result;
+use diesel::{
+    prelude::*,
+    sql_types::{Double, Text},
+};
+use std::sync::Arc;
+
+use crate::{
+    domain::{
+        entities::envelope_transfers::{AddEnvelopeTransferDto, EnvelopeTransfer},
+        repositories::envelope::EnvelopeRepository,
+    },
+    infrastructure::database::{SqlitePoolSquad, schema::envelope_transfers},
+};
+
+#[derive(Clone)]
+pub struct EnvelopeSqlite {
+    db_pool: Arc<SqlitePoolSquad>,
+}
+
+impl EnvelopeSqlite {
+    pub fn new(db_pool: Arc<SqlitePoolSquad>) -> Self {
+        Self { db_pool }
+    }
+}
+
+#[derive(QueryableByName)]
+struct Balance {
+    #[diesel(sql_type = Double)]
+    balance: f64,
+}
+
+/// What an envelope, or the unassigned pool for `None`, has left to give, worked out the same
+/// way as the envelope status: an envelope pays for its category from the day it was opened,
+/// every other expense comes out of the pool.
+fn balance(conn: &mut SqliteConnection, envelope: Option<String>) -> QueryResult<f32> {
+    // Day each envelope first had money moved in or out
+    let opened = "SELECT envelope, MIN(date) AS opened_on FROM ( \
+            SELECT from_envelope AS envelope, date FROM envelope_transfers \
+            UNION ALL \
+            SELECT to_envelope AS envelope, date FROM envelope_transfers \
+        ) \
+        WHERE envelope IS NOT NULL \
+        GROUP BY envelope";
+
+    let result = match envelope {
+        Some(envelope) => diesel::sql_query(format!(
+            "SELECT \
+                COALESCE((SELECT SUM(CASE WHEN to_envelope = ?1 THEN amount ELSE -amount END) \
+                    FROM envelope_transfers \
+                    WHERE from_envelope = ?1 OR to_envelope = ?1), 0) \
+                + COALESCE((SELECT SUM(my_ledger.amount) \
+                    FROM my_ledger \
+                    JOIN ({}) opened ON opened.envelope = my_ledger.category \
+                    WHERE my_ledger.amount < 0 \
+                        AND my_ledger.category = ?1 \
+                        AND my_ledger.date >= opened.opened_on), 0) AS balance",
+            opened
+        ))
+        .bind::<Text, _>(envelope)
+        .get_result::<Balance>(conn)?,
+        None => diesel::sql_query(format!(
+            "SELECT \
+                COALESCE((SELECT SUM(amount) FROM my_ledger), 0) \
+                - COALESCE((SELECT SUM(CASE WHEN from_envelope IS NULL THEN amount ELSE -amount END) \
+                    FROM envelope_transfers \
+                    WHERE from_envelope IS NULL OR to_envelope IS NULL), 0) \
+                - COALESCE((SELECT SUM(my_ledger.amount) \
+                    FROM my_ledger \
+                    JOIN ({}) opened ON opened.envelope = my_ledger.category \
+                    WHERE my_ledger.amount < 0 \
+                        AND my_ledger.date >= opened.opened_on), 0) AS balance",
+            opened
+        ))
+        .get_result::<Balance>(conn)?,
+    };
+
+    // Compared at the precision the status reports balances in
+    Ok(result.balance as f32)
+}
+
+#[async_trait::async_trait]
+impl EnvelopeRepository for EnvelopeSqlite {
+    async fn view_all_transfers(&self) -> Result<Vec<EnvelopeTransfer>> {
+        let conn = &mut self.db_pool.get()?;
+
+        let results = envelope_transfers::table
+            .order(envelope_transfers::id.asc())
+            .select(EnvelopeTransfer::as_select())
+            .load::<EnvelopeTransfer>(conn)?;
+
+        Ok(results)
+    }
+
+    async fn add_transfer(
+        &self,
+        add_envelope_transfer_dto: AddEnvelopeTransferDto,
+    ) -> Result<Option<i32>> {
+        let conn = &mut self.db_pool.get()?;
+
+        conn.immediate_transaction(|conn| {
+            let available = balance(conn, add_envelope_transfer_dto.from_envelope.to_owned())?;
+            if add_envelope_transfer_dto.amount > available {
+                return Ok(None);
+            }
+
+            let result_id = diesel::insert_into(envelope_transfers::table)
+                .values(&add_envelope_transfer_dto)
+                .returning(envelope_transfers::id)
+                .get_result::<i32>(conn)?;
+
+            Ok(Some(result_id))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use diesel::prelude::*;
+
+    use crate::{
+        domain::{
+            entities::{envelope_transfers::AddEnvelopeTransferDto, my_ledger::RecordMyLedgerDto},
+            repositories::envelope::EnvelopeRepository,
+        },
+        infrastructure::database::{
+            repositories::envelope::EnvelopeSqlite, schema::my_ledger, test_pool,
+        },
+    };
+
+    fn transfer(from: Option<&str>, to: Option<&str>, amount: f32) -> AddEnvelopeTransferDto {
+        AddEnvelopeTransferDto {
+            from_envelope: from.map(str::to_string),
+            to_envelope: to.map(str::to_string),
+            amount,
+            date: "2025-04-01".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_transfer_checks_the_source_balance() {
+        let db_pool = Arc::new(test_pool());
+
+        {
+            let conn = &mut db_pool.get().unwrap();
+
+            // COFFEE is spent before its envelope is opened, so it comes out of the pool
+            let entries = [
+                (30000.0, "SALARY", "2025-03-25"),
+                (-500.0, "COFFEE", "2025-03-28"),
+                (-2500.0, "FOOD", "2025-04-10"),
+            ]
+            .map(|(amount, category, date)| RecordMyLedgerDto {
+                amount,
+                category: category.to_string(),
+                description: "Test".to_string(),
+                date: date.to_string(),
+                income_type: None,
+            });
+
+            diesel::insert_into(my_ledger::table)
+                .values(&entries[..])
+                .execute(conn)
+                .unwrap();
+        }
+
+        let envelope_repository = EnvelopeSqlite::new(Arc::clone(&db_pool));
+        let add = |from: Option<&str>, to: Option<&str>, amount: f32| {
+            envelope_repository.add_transfer(transfer(from, to, amount))
+        };
+
+        assert!(add(None, Some("FOOD"), 8000.0).await.unwrap().is_some());
+        assert!(add(None, Some("RENT"), 12000.0).await.unwrap().is_some());
+        assert!(
+            add(Some("RENT"), Some("FOOD"), 1000.0)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        // 30000 - 500 - 20000 assigned leaves 9500 in the pool
+        assert!(add(None, Some("COFFEE"), 9500.01).await.unwrap().is_none());
+        assert!(add(None, Some("COFFEE"), 9500.0).await.unwrap().is_some());
+        assert!(add(None, Some("COFFEE"), 1.0).await.unwrap().is_none());
+
+        // 9000 assigned to FOOD less the 2500 spent since it was opened
+        assert!(add(Some("FOOD"), None, 6500.01).await.unwrap().is_none());
+        assert!(add(Some("FOOD"), None, 6500.0).await.unwrap().is_some());
+        assert!(
+            add(Some("TRAVEL"), Some("FOOD"), 1.0)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // The money returned to the pool can be assigned again
+        assert!(add(None, Some("TRAVEL"), 6500.0).await.unwrap().is_some());
+    }
+}
@@ -1,8 +1,10 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use chrono::Local;
 use diesel::{
-    dsl::{delete, insert_into},
+    dsl::{self, case_when, count_star, delete, insert_into, update},
     prelude::*,
+    sql_types::{BigInt, Nullable, Text},
+    sqlite::Sqlite,
 };
 use std::sync::Arc;
 
@@ -10,13 +12,19 @@ use crate::{
     domain::{
         entities::{
             monthly_spending::{AddMonthlySpendingDto, MonthlySpending},
-            my_ledger::MyLedger,
+            my_ledger::{
+                CategoryCashFlow, CategoryTotal, MerchantSummary, MyLedger, TransactionMatch,
+            },
         },
         repositories::spending_scanner::SpendingScannerRepository,
+        value_objects::{
+            bills::BillStatus,
+            spending_scanner::{Grouping, MerchantRanking},
+        },
     },
     infrastructure::database::{
-        SqlitePoolSquad,
-        schema::{monthly_spending, my_ledger},
+        SqlitePoolSquad, date_bounds,
+        schema::{bill_occurrences, monthly_spending, my_ledger},
     },
 };
 
@@ -31,15 +39,33 @@ impl SpendingScannerSqlite {
     }
 }
 
+fn ledger_between(start: String, end: String) -> my_ledger::BoxedQuery<'static, Sqlite> {
+    my_ledger::table
+        .filter(my_ledger::date.ge(start))
+        .filter(my_ledger::date.lt(end))
+        .order(my_ledger::date.desc())
+        .into_boxed()
+}
+
+/// Turns free text into an FTS5 query: every word must match, as a prefix so "shoe" finds
+/// "shoes". Quoting each word keeps punctuation from being read as FTS5 syntax.
+fn fts_query(query: &str) -> String {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"*", word))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
 #[async_trait::async_trait]
 impl SpendingScannerRepository for SpendingScannerSqlite {
     async fn today(&self) -> Result<Vec<MyLedger>> {
         let conn = &mut self.db_pool.get()?;
-        let today_prefix = Local::now().format("%Y-%m-%d").to_string(); // e.g. "2025-04-11"
+        let (start, end) = date_bounds(Grouping::Day, Local::now().date_naive());
 
-        let result = my_ledger::table
-            .filter(my_ledger::date.like(format!("{}%", today_prefix)))
-            .order(my_ledger::date.desc())
+        let result = ledger_between(start, end)
+            .select(MyLedger::as_select())
             .load::<MyLedger>(conn)?;
 
         Ok(result)
@@ -47,11 +73,10 @@ impl SpendingScannerRepository for SpendingScannerSqlite {
 
     async fn this_month(&self) -> Result<Vec<MyLedger>> {
         let conn = &mut self.db_pool.get()?;
-        let this_month_prefix = Local::now().format("%Y-%m").to_string(); // e.g. "2025-04"
+        let (start, end) = date_bounds(Grouping::Month, Local::now().date_naive());
 
-        let result = my_ledger::table
-            .filter(my_ledger::date.like(format!("{}%", this_month_prefix)))
-            .order(my_ledger::date.desc())
+        let result = ledger_between(start, end)
+            .select(MyLedger::as_select())
             .load::<MyLedger>(conn)?;
 
         Ok(result)
@@ -59,11 +84,10 @@ impl SpendingScannerRepository for SpendingScannerSqlite {
 
     async fn this_year(&self) -> Result<Vec<MyLedger>> {
         let conn = &mut self.db_pool.get()?;
-        let this_year_prefix = Local::now().format("%Y").to_string(); // e.g. "2025"
+        let (start, end) = date_bounds(Grouping::Year, Local::now().date_naive());
 
-        let result = my_ledger::table
-            .filter(my_ledger::date.like(format!("{}%", this_year_prefix)))
-            .order(my_ledger::date.desc())
+        let result = ledger_between(start, end)
+            .select(MyLedger::as_select())
             .load::<MyLedger>(conn)?;
 
         Ok(result)
@@ -74,6 +98,7 @@ impl SpendingScannerRepository for SpendingScannerSqlite {
 
         let result = my_ledger::table
             .order(my_ledger::date.desc())
+            .select(MyLedger::as_select())
             .load::<MyLedger>(conn)?;
 
         Ok(result)
@@ -82,19 +107,140 @@ impl SpendingScannerRepository for SpendingScannerSqlite {
     async fn custom(&self, start: String, end: String) -> Result<Vec<MyLedger>> {
         let conn = &mut self.db_pool.get()?;
 
+        let result = ledger_between(start, end)
+            .select(MyLedger::as_select())
+            .load::<MyLedger>(conn)?;
+
+        Ok(result)
+    }
+
+    async fn sum_by_category(&self, start: String, end: String) -> Result<Vec<CategoryTotal>> {
+        let conn = &mut self.db_pool.get()?;
+
         let result = my_ledger::table
-            .filter(my_ledger::date.between(start, end))
-            .order(my_ledger::date.desc())
+            .filter(my_ledger::date.ge(start))
+            .filter(my_ledger::date.lt(end))
+            .group_by(my_ledger::category)
+            .select((my_ledger::category, dsl::sum(my_ledger::amount)))
+            .load::<CategoryTotal>(conn)?;
+
+        Ok(result)
+    }
+
+    async fn cash_flow_by_category(
+        &self,
+        start: String,
+        end: String,
+    ) -> Result<Vec<CategoryCashFlow>> {
+        let conn = &mut self.db_pool.get()?;
+
+        let result = my_ledger::table
+            .filter(my_ledger::date.ge(start))
+            .filter(my_ledger::date.lt(end))
+            .group_by(my_ledger::category)
+            .select((
+                my_ledger::category,
+                dsl::sum(case_when(my_ledger::amount.lt(0.0), my_ledger::amount).otherwise(0.0)),
+                dsl::sum(case_when(my_ledger::amount.gt(0.0), my_ledger::amount).otherwise(0.0)),
+            ))
+            .load::<CategoryCashFlow>(conn)?;
+
+        Ok(result)
+    }
+
+    async fn top_expenses(&self, start: String, end: String, limit: i64) -> Result<Vec<MyLedger>> {
+        let conn = &mut self.db_pool.get()?;
+
+        let result = my_ledger::table
+            .filter(my_ledger::amount.lt(0.0))
+            .filter(my_ledger::date.ge(start))
+            .filter(my_ledger::date.lt(end))
+            .order(my_ledger::amount.asc())
+            .limit(limit)
+            .select(MyLedger::as_select())
             .load::<MyLedger>(conn)?;
 
         Ok(result)
     }
 
+    async fn top_merchants(
+        &self,
+        start: String,
+        end: String,
+        limit: i64,
+        rank_by: MerchantRanking,
+    ) -> Result<Vec<MerchantSummary>> {
+        let conn = &mut self.db_pool.get()?;
+
+        let query = my_ledger::table
+            .filter(my_ledger::amount.lt(0.0))
+            .filter(my_ledger::date.ge(start))
+            .filter(my_ledger::date.lt(end))
+            .group_by(my_ledger::description)
+            .select((
+                my_ledger::description,
+                count_star(),
+                dsl::sum(my_ledger::amount),
+                dsl::avg(my_ledger::amount),
+            ))
+            .limit(limit);
+
+        // Expenses are negative, so the most expensive merchant has the lowest sum.
+        let result = match rank_by {
+            MerchantRanking::Frequency => query
+                .order((count_star().desc(), dsl::sum(my_ledger::amount).asc()))
+                .load::<MerchantSummary>(conn)?,
+            MerchantRanking::Total => query
+                .order(dsl::sum(my_ledger::amount).asc())
+                .load::<MerchantSummary>(conn)?,
+        };
+
+        Ok(result)
+    }
+
+    async fn search(
+        &self,
+        query: String,
+        start: String,
+        end: String,
+        category: Option<String>,
+        limit: i64,
+    ) -> Result<Vec<TransactionMatch>> {
+        let match_query = fts_query(&query);
+        if match_query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = &mut self.db_pool.get()?;
+
+        let result = diesel::sql_query(
+            "SELECT my_ledger.id, my_ledger.amount, my_ledger.category, my_ledger.description, \
+                my_ledger.date, snippet(my_ledger_fts, 0, '[', ']', '...', 8) AS snippet, \
+                bm25(my_ledger_fts) AS rank \
+            FROM my_ledger_fts \
+            JOIN my_ledger ON my_ledger.id = my_ledger_fts.rowid \
+            WHERE my_ledger_fts MATCH ? \
+                AND my_ledger.date >= ? AND my_ledger.date < ? \
+                AND (? IS NULL OR my_ledger.category = ?) \
+            ORDER BY rank, my_ledger.date DESC \
+            LIMIT ?",
+        )
+        .bind::<Text, _>(match_query)
+        .bind::<Text, _>(start)
+        .bind::<Text, _>(end)
+        .bind::<Nullable<Text>, _>(category.clone())
+        .bind::<Nullable<Text>, _>(category)
+        .bind::<BigInt, _>(limit)
+        .load::<TransactionMatch>(conn)?;
+
+        Ok(result)
+    }
+
     async fn view_all_monthly_spending(&self) -> Result<Vec<MonthlySpending>> {
         let conn = &mut self.db_pool.get()?;
 
         let result = monthly_spending::table
-            .order(monthly_spending::due_date.asc())
+            .order(monthly_spending::anchor_date.asc())
             .load::<MonthlySpending>(conn)?;
 
         Ok(result)
@@ -114,13 +260,290 @@ impl SpendingScannerRepository for SpendingScannerSqlite {
         Ok(result_id)
     }
 
+    async fn update_monthly_spending(
+        &self,
+        id: i32,
+        update_monthly_spending_dto: AddMonthlySpendingDto,
+        reschedule_from: Option<String>,
+    ) -> Result<()> {
+        let conn = &mut self.db_pool.get()?;
+
+        conn.transaction(|conn| {
+            let updated = update(monthly_spending::table.filter(monthly_spending::id.eq(id)))
+                .set(&update_monthly_spending_dto)
+                .execute(conn)?;
+            if updated == 0 {
+                return Err(anyhow!("No monthly spending with id {}", id));
+            }
+
+            if let Some(reschedule_from) = reschedule_from {
+                delete(bill_occurrences::table)
+                    .filter(bill_occurrences::monthly_spending_id.eq(id))
+                    .filter(bill_occurrences::status.eq(BillStatus::Pending.as_str()))
+                    .filter(bill_occurrences::due_date.ge(reschedule_from))
+                    .execute(conn)?;
+            }
+
+            Ok(())
+        })
+    }
+
     async fn remove_monthly_spending(&self, id: i32) -> Result<()> {
         let conn = &mut self.db_pool.get()?;
 
-        delete(monthly_spending::table)
-            .filter(monthly_spending::id.eq(id))
-            .execute(conn)?;
+        // SQLite does not enforce the foreign key unless asked to, so occurrences go explicitly
+        conn.transaction(|conn| {
+            delete(bill_occurrences::table)
+                .filter(bill_occurrences::monthly_spending_id.eq(id))
+                .execute(conn)?;
+            delete(monthly_spending::table)
+                .filter(monthly_spending::id.eq(id))
+                .execute(conn)
+        })?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use diesel::{debug_query, prelude::*, sql_types::Text, sqlite::Sqlite};
+
+    use crate::{
+        domain::{
+            entities::{
+                bill_occurrences::{AddBillOccurrenceDto, SettleBillOccurrenceDto},
+                monthly_spending::AddMonthlySpendingDto,
+                my_ledger::RecordMyLedgerDto,
+            },
+            repositories::{bills::BillRepository, spending_scanner::SpendingScannerRepository},
+        },
+        infrastructure::database::{
+            repositories::{
+                bills::BillSqlite,
+                spending_scanner::{SpendingScannerSqlite, ledger_between},
+            },
+            schema::my_ledger,
+            test_pool,
+        },
+    };
+
+    #[derive(QueryableByName)]
+    struct QueryPlan {
+        #[diesel(sql_type = Text)]
+        detail: String,
+    }
+
+    #[test]
+    fn test_ledger_between_uses_date_index() {
+        let conn = &mut test_pool().get().unwrap();
+
+        let query = ledger_between("2025-04-01".to_string(), "2025-05-01".to_string());
+        let sql = debug_query::<Sqlite, _>(&query).to_string();
+
+        let plan = diesel::sql_query(format!("EXPLAIN QUERY PLAN {}", sql))
+            .load::<QueryPlan>(conn)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.detail)
+            .collect::<Vec<String>>();
+
+        assert!(
+            plan.iter()
+                .any(|detail| detail.contains("USING INDEX idx_my_ledger_date")),
+            "{:?}",
+            plan
+        );
+        assert!(
+            !plan.iter().any(|detail| detail.contains("TEMP B-TREE")),
+            "{:?}",
+            plan
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_follows_ledger_changes() {
+        let db_pool = Arc::new(test_pool());
+
+        {
+            let conn = &mut db_pool.get().unwrap();
+
+            let entries = [
+                ("SHOPPING", "Running shoes at Nike", "2025-03-02"),
+                ("SHOPPING", "Shoe polish", "2025-04-10"),
+                ("FOOD", "KFC bucket", "2025-04-11"),
+            ]
+            .map(|(category, description, date)| RecordMyLedgerDto {
+                amount: -100.0,
+                category: category.to_string(),
+                description: description.to_string(),
+                date: date.to_string(),
+                income_type: None,
+            });
+
+            diesel::insert_into(my_ledger::table)
+                .values(&entries[..])
+                .execute(conn)
+                .unwrap();
+
+            diesel::update(my_ledger::table.filter(my_ledger::id.eq(2)))
+                .set(my_ledger::description.eq("Sock polish"))
+                .execute(conn)
+                .unwrap();
+            diesel::delete(my_ledger::table.filter(my_ledger::id.eq(3)))
+                .execute(conn)
+                .unwrap();
+        }
+
+        let spending_scanner = SpendingScannerSqlite::new(Arc::clone(&db_pool));
+        let search = |query: &str| {
+            spending_scanner.search(
+                query.to_string(),
+                "0001-01-01".to_string(),
+                "9999-12-31".to_string(),
+                Some("SHOPPING".to_string()),
+                10,
+            )
+        };
+
+        let shoes = search("shoe's").await.unwrap();
+        assert_eq!(shoes.len(), 1);
+        assert_eq!(shoes[0].id, 1);
+        assert_eq!(shoes[0].snippet, "Running [shoes] at Nike");
+
+        assert_eq!(search("sock").await.unwrap().len(), 1);
+        assert!(search("kfc").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cash_flow_by_category_keeps_income_apart() {
+        let db_pool = Arc::new(test_pool());
+
+        {
+            let conn = &mut db_pool.get().unwrap();
+
+            let entries = [
+                (-1000.0, "SHOPPING", "2025-04-02"),
+                (400.0, "SHOPPING", "2025-04-05"),
+                (-250.0, "FOOD", "2025-04-06"),
+                (-50.0, "FOOD", "2025-04-30 10:00"),
+                (-999.0, "FOOD", "2025-05-01"),
+            ]
+            .map(|(amount, category, date)| RecordMyLedgerDto {
+                amount,
+                category: category.to_string(),
+                description: "Test".to_string(),
+                date: date.to_string(),
+                income_type: None,
+            });
+
+            diesel::insert_into(my_ledger::table)
+                .values(&entries[..])
+                .execute(conn)
+                .unwrap();
+        }
+
+        let spending_scanner = SpendingScannerSqlite::new(Arc::clone(&db_pool));
+
+        let mut cash_flows = spending_scanner
+            .cash_flow_by_category("2025-04-01".to_string(), "2025-05-01".to_string())
+            .await
+            .unwrap();
+        cash_flows.sort_by(|a, b| a.category.cmp(&b.category));
+
+        assert_eq!(cash_flows.len(), 2);
+        assert_eq!(cash_flows[0].category, "FOOD");
+        assert_eq!(cash_flows[0].expense, Some(-300.0));
+        assert_eq!(cash_flows[0].income, Some(0.0));
+        assert_eq!(cash_flows[1].expense, Some(-1000.0));
+        assert_eq!(cash_flows[1].income, Some(400.0));
+    }
+
+    #[tokio::test]
+    async fn test_update_monthly_spending_drops_pending_cycles() {
+        let db_pool = Arc::new(test_pool());
+        let spending_scanner_repository = SpendingScannerSqlite::new(Arc::clone(&db_pool));
+        let bill_repository = BillSqlite::new(Arc::clone(&db_pool));
+
+        let electricity = |anchor_date: &str| AddMonthlySpendingDto {
+            title: "Electricity".to_string(),
+            amount: 900.0,
+            frequency: "Monthly".to_string(),
+            interval: 1,
+            anchor_date: anchor_date.to_string(),
+            end_date: None,
+            count: None,
+            category: "BILLS".to_string(),
+            posting: "ConfirmFirst".to_string(),
+            post_from: "2025-01-01".to_string(),
+        };
+
+        let id = spending_scanner_repository
+            .add_monthly_spending(electricity("2025-01-10"))
+            .await
+            .unwrap();
+        bill_repository
+            .add_occurrences(
+                ["2025-01-10", "2025-02-10", "2025-03-10"]
+                    .map(|due_date| AddBillOccurrenceDto {
+                        monthly_spending_id: id,
+                        due_date: due_date.to_string(),
+                    })
+                    .to_vec(),
+            )
+            .await
+            .unwrap();
+        let paid = bill_repository
+            .find_occurrence(id, "2025-01-10".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        bill_repository
+            .settle_occurrence(
+                paid.id,
+                SettleBillOccurrenceDto {
+                    status: "Paid".to_string(),
+                    paid_date: Some("2025-01-10".to_string()),
+                    ledger_id: None,
+                },
+            )
+            .await
+            .unwrap();
+
+        // Rescheduled on Mar 1: February is overdue and stays owed, March follows the new day
+        spending_scanner_repository
+            .update_monthly_spending(
+                id,
+                electricity("2025-02-20"),
+                Some("2025-03-01".to_string()),
+            )
+            .await
+            .unwrap();
+
+        let items = spending_scanner_repository
+            .view_all_monthly_spending()
+            .await
+            .unwrap();
+        assert_eq!(items[0].anchor_date, "2025-02-20");
+
+        let pending = bill_repository
+            .view_pending_occurrences("2025-12-31".to_string())
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].due_date, "2025-02-10");
+
+        let kept = bill_repository
+            .find_occurrence(id, "2025-01-10".to_string())
+            .await
+            .unwrap();
+        assert!(kept.is_some());
+
+        let missing = spending_scanner_repository
+            .update_monthly_spending(id + 1, electricity("2025-02-20"), None)
+            .await;
+        assert!(missing.is_err());
+    }
+}
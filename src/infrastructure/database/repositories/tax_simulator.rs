@@ -1,21 +1,25 @@
-use diesel::{dsl::delete, prelude::*};
+use diesel::{
+    dsl::{self, delete},
+    prelude::*,
+};
 use std::sync::Arc;
 
 use crate::{
     domain::{
         entities::{
-            my_ledger::MyLedger,
+            my_ledger::{IncomeLedger, IncomeTypeTotal},
             tax_deductions_list::{AddTaxDeductionsListDto, TaxDeductionsList},
         },
         repositories::tax_simulator::TaxSimulatorRepository,
+        value_objects::spending_scanner::Grouping,
     },
     infrastructure::database::{
-        SqlitePoolSquad,
+        SqlitePoolSquad, date_bounds,
         schema::{my_ledger, tax_deductions_list},
     },
 };
-use anyhow::Result;
-use diesel::TextExpressionMethods;
+use anyhow::{Result, anyhow};
+use chrono::NaiveDate;
 
 #[derive(Clone)]
 pub struct TaxSimulatorSqlite {
@@ -28,17 +32,41 @@ impl TaxSimulatorSqlite {
     }
 }
 
+fn year_bounds(year: i32) -> Result<(String, String)> {
+    let first_day =
+        NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(|| anyhow!("Invalid year: {}", year))?;
+
+    Ok(date_bounds(Grouping::Year, first_day))
+}
+
 #[async_trait::async_trait]
 impl TaxSimulatorRepository for TaxSimulatorSqlite {
-    async fn view_all_income_by_year(&self, year: i32) -> Result<Vec<MyLedger>> {
+    async fn view_all_income_by_year(&self, year: i32) -> Result<Vec<IncomeLedger>> {
         let conn = &mut self.db_pool.get()?;
+        let (start, end) = year_bounds(year)?;
 
         let results = my_ledger::table
             .filter(my_ledger::amount.gt(0.0))
-            .filter(my_ledger::date.like(format!("{}%", year)))
+            .filter(my_ledger::date.ge(start))
+            .filter(my_ledger::date.lt(end))
             .order(my_ledger::date.desc())
-            .select(MyLedger::as_select())
-            .load::<MyLedger>(conn)?;
+            .select(IncomeLedger::as_select())
+            .load::<IncomeLedger>(conn)?;
+
+        Ok(results)
+    }
+
+    async fn income_by_type(&self, year: i32) -> Result<Vec<IncomeTypeTotal>> {
+        let conn = &mut self.db_pool.get()?;
+        let (start, end) = year_bounds(year)?;
+
+        let results = my_ledger::table
+            .filter(my_ledger::amount.gt(0.0))
+            .filter(my_ledger::date.ge(start))
+            .filter(my_ledger::date.lt(end))
+            .group_by(my_ledger::income_type)
+            .select((my_ledger::income_type, dsl::sum(my_ledger::amount)))
+            .load::<IncomeTypeTotal>(conn)?;
 
         Ok(results)
     }
@@ -78,3 +106,61 @@ impl TaxSimulatorRepository for TaxSimulatorSqlite {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use diesel::prelude::*;
+
+    use crate::{
+        domain::{
+            entities::my_ledger::RecordMyLedgerDto,
+            repositories::tax_simulator::TaxSimulatorRepository,
+        },
+        infrastructure::database::{
+            repositories::tax_simulator::TaxSimulatorSqlite, schema::my_ledger, test_pool,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_income_by_type_sums_income_of_the_year() {
+        let db_pool = Arc::new(test_pool());
+
+        {
+            let conn = &mut db_pool.get().unwrap();
+
+            let entries = [
+                (30_000.0, None, "2025-01-25"),
+                (30_000.0, None, "2025-12-31 18:00"),
+                (12_000.0, Some("RentBuilding"), "2025-03-01"),
+                (8_000.0, Some("RentBuilding"), "2025-04-01"),
+                (-500.0, Some("RentBuilding"), "2025-04-02"),
+                (99_000.0, None, "2026-01-01"),
+            ]
+            .map(|(amount, income_type, date)| RecordMyLedgerDto {
+                amount,
+                category: "INCOME".to_string(),
+                description: "Test".to_string(),
+                date: date.to_string(),
+                income_type: income_type.map(str::to_string),
+            });
+
+            diesel::insert_into(my_ledger::table)
+                .values(&entries[..])
+                .execute(conn)
+                .unwrap();
+        }
+
+        let tax_simulator = TaxSimulatorSqlite::new(Arc::clone(&db_pool));
+
+        let mut totals = tax_simulator.income_by_type(2025).await.unwrap();
+        totals.sort_by(|a, b| a.income_type.cmp(&b.income_type));
+
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].income_type, None);
+        assert_eq!(totals[0].total, Some(60_000.0));
+        assert_eq!(totals[1].income_type.as_deref(), Some("RentBuilding"));
+        assert_eq!(totals[1].total, Some(20_000.0));
+    }
+}
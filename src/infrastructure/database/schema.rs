@@ -1,11 +1,51 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    bill_occurrences (id) {
+        id -> Integer,
+        monthly_spending_id -> Integer,
+        due_date -> Text,
+        ledger_id -> Nullable<Integer>,
+        status -> Text,
+        paid_date -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    budgets (id) {
+        id -> Integer,
+        category -> Text,
+        monthly_limit -> Float,
+        effective_from -> Nullable<Text>,
+        effective_to -> Nullable<Text>,
+        rollover -> Text,
+        rollover_cap -> Nullable<Float>,
+    }
+}
+
+diesel::table! {
+    envelope_transfers (id) {
+        id -> Integer,
+        from_envelope -> Nullable<Text>,
+        to_envelope -> Nullable<Text>,
+        amount -> Float,
+        date -> Text,
+    }
+}
+
 diesel::table! {
     monthly_spending (id) {
         id -> Integer,
         title -> Text,
         amount -> Float,
-        due_date -> Text,
+        frequency -> Text,
+        interval -> Integer,
+        anchor_date -> Text,
+        end_date -> Nullable<Text>,
+        count -> Nullable<Integer>,
+        category -> Text,
+        posting -> Text,
+        post_from -> Text,
     }
 }
 
@@ -16,6 +56,7 @@ diesel::table! {
         category -> Text,
         description -> Text,
         date -> Text,
+        income_type -> Nullable<Text>,
     }
 }
 
@@ -27,7 +68,13 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(bill_occurrences -> monthly_spending (monthly_spending_id));
+diesel::joinable!(bill_occurrences -> my_ledger (ledger_id));
+
 diesel::allow_tables_to_appear_in_same_query!(
+    bill_occurrences,
+    budgets,
+    envelope_transfers,
     monthly_spending,
     my_ledger,
     tax_deductions_list,
@@ -1,11 +1,35 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
+use crate::domain::value_objects::bills::{
+    ExportBillsCalendarModel, MarkBillPaidModel, PostDueBillsModel, UpcomingBillsFilter,
+};
+use crate::domain::value_objects::budget::{BudgetStatusFilter, SetBudgetModel};
+use crate::domain::value_objects::envelope::{AssignEnvelopeModel, MoveEnvelopeModel};
 use crate::domain::value_objects::spending_scanner::{
-    AddMonthlySpendingModel, SpendingScannerFilter,
+    AddMonthlySpendingModel, CompareSpendingModel, SearchTransactionsModel, SpendingScannerFilter,
+    SpendingTimeSeriesFilter, TopSpendingFilter, UpdateMonthlySpendingModel,
 };
+use crate::domain::value_objects::subscription::SubscriptionFilter;
 use crate::domain::value_objects::tax_simulator::{
-    AddTaxDeductionsListModel, RemoveTaxDeductionsListModel, TaxSimulateRequestModel,
+    AddTaxDeductionsListModel, IncomeByYearFilter, RemoveTaxDeductionsListModel,
+    TaxSimulateRequestModel,
+};
+use crate::domain::value_objects::validation::ValidationErrors;
+use crate::domain::{
+    entities::my_ledger::RecordMyLedgerDto,
+    value_objects::{
+        cash_flow::CashFlowWarningModel,
+        chart::{
+            ChartFormat, OutputFormat, SpendingChartModel, SpendingTimeSeriesModel,
+            SpendingVisualizerModel,
+        },
+        spending_scanner::{Grouping, Range},
+        unusual_spending::UnusualSpendingFilter,
+    },
 };
+use crate::infrastructure::{calendar_exporter, chart_renderer};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use chrono::Utc;
 use rmcp::handler::server::router::prompt::PromptRouter;
 use rmcp::handler::server::tool::ToolRouter;
 use rmcp::handler::server::wrapper::Parameters;
@@ -18,8 +42,10 @@ use serde_json::json;
 
 use crate::{
     application::use_cases::{
-        cash_flow::CashFlowUseCase, spending_scanner::SpendingScannerUseCase,
-        tax_simulator::TaxSimulatorUseCase,
+        bills::BillsUseCase, budget::BudgetUseCase, cash_flow::CashFlowUseCase,
+        envelope::EnvelopeUseCase, spending_scanner::SpendingScannerUseCase,
+        subscription::SubscriptionUseCase, tax_simulator::TaxSimulatorUseCase,
+        unusual_spending::UnusualSpendingUseCase,
     },
     domain::value_objects::{
         cash_flow::{RecordCashFlowModel, RecordCashFlowWithDateModel},
@@ -27,26 +53,48 @@ use crate::{
     },
 };
 
+const DEFAULT_CALENDAR_PATH: &str = "bills.ics";
+const DEFAULT_REMINDER_DAYS: u32 = 1;
+
 #[derive(Clone)]
 pub struct MCPHandler {
     cash_flow_use_case: Arc<CashFlowUseCase>,
     spending_scanner_use_case: Arc<SpendingScannerUseCase>,
     tax_simulator_use_case: Arc<TaxSimulatorUseCase>,
+    unusual_spending_use_case: Arc<UnusualSpendingUseCase>,
+    budget_use_case: Arc<BudgetUseCase>,
+    envelope_use_case: Arc<EnvelopeUseCase>,
+    bills_use_case: Arc<BillsUseCase>,
+    subscription_use_case: Arc<SubscriptionUseCase>,
+    export_dir: PathBuf,
     tool_router: ToolRouter<MCPHandler>,
     prompt_router: PromptRouter<MCPHandler>,
 }
 
 #[tool_router]
 impl MCPHandler {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         cash_flow_use_case: Arc<CashFlowUseCase>,
         spending_scanner_use_case: Arc<SpendingScannerUseCase>,
         tax_simulator_use_case: Arc<TaxSimulatorUseCase>,
+        unusual_spending_use_case: Arc<UnusualSpendingUseCase>,
+        budget_use_case: Arc<BudgetUseCase>,
+        envelope_use_case: Arc<EnvelopeUseCase>,
+        bills_use_case: Arc<BillsUseCase>,
+        subscription_use_case: Arc<SubscriptionUseCase>,
+        export_dir: PathBuf,
     ) -> Self {
         Self {
             cash_flow_use_case,
             spending_scanner_use_case,
             tax_simulator_use_case,
+            unusual_spending_use_case,
+            budget_use_case,
+            envelope_use_case,
+            bills_use_case,
+            subscription_use_case,
+            export_dir,
             tool_router: Self::tool_router(),
             prompt_router: Self::prompt_router(),
         }
@@ -56,16 +104,68 @@ impl MCPHandler {
         RawResource::new(uri, name.to_string()).no_annotation()
     }
 
+    /// Warnings are best effort, a failing check must not hide that the entry was recorded.
+    async fn record_warnings(
+        &self,
+        id: i32,
+        record_my_ledger_dto: RecordMyLedgerDto,
+    ) -> Vec<CashFlowWarningModel> {
+        let mut warnings = Vec::new();
+
+        match self
+            .unusual_spending_use_case
+            .check_recorded(id, record_my_ledger_dto.clone())
+            .await
+        {
+            Ok(Some(unusual)) => warnings.push(CashFlowWarningModel::UnusualSpending(unusual)),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("unusual spending check failed: {:?}", e),
+        }
+
+        match self
+            .budget_use_case
+            .check_recorded(record_my_ledger_dto)
+            .await
+        {
+            Ok(Some(budget)) => warnings.push(CashFlowWarningModel::BudgetThreshold(budget)),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("budget check failed: {:?}", e),
+        }
+
+        warnings
+    }
+
+    async fn recorded_result(
+        &self,
+        id: i32,
+        record_my_ledger_dto: RecordMyLedgerDto,
+    ) -> CallToolResult {
+        let mut contents = vec![Content::text(format!(
+            "Cash flow ledger transaction recorded successfully: id: {}",
+            id
+        ))];
+
+        // The entry is already written, failing the call now would invite a duplicate retry
+        let warnings = self.record_warnings(id, record_my_ledger_dto).await;
+        if !warnings.is_empty() {
+            match Content::json(json!({ "warnings": warnings })) {
+                Ok(res_json) => contents.push(res_json),
+                Err(e) => tracing::warn!("dropping record warnings: {:?}", e),
+            }
+        }
+
+        CallToolResult::success(contents)
+    }
+
     #[tool(description = "Record a cash flow ledger transaction")]
     pub async fn record_cash_flow(
         &self,
         Parameters(record_cash_flow_model): Parameters<RecordCashFlowModel>,
     ) -> Result<CallToolResult, McpError> {
+        let record_my_ledger_dto = record_cash_flow_model.to_dto();
+
         match self.cash_flow_use_case.record(record_cash_flow_model).await {
-            Ok(id) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Cash flow ledger transaction recorded successfully: id: {}",
-                id
-            ))])),
+            Ok(id) => Ok(self.recorded_result(id, record_my_ledger_dto).await),
             Err(e) => Err(McpError::internal_error(e.to_string(), None)),
         }
     }
@@ -75,15 +175,14 @@ impl MCPHandler {
         &self,
         Parameters(record_cash_flow_with_date_model): Parameters<RecordCashFlowWithDateModel>,
     ) -> Result<CallToolResult, McpError> {
+        let record_my_ledger_dto = record_cash_flow_with_date_model.to_dto();
+
         match self
             .cash_flow_use_case
             .record_with_date(record_cash_flow_with_date_model)
             .await
         {
-            Ok(id) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Cash flow ledger transaction recorded successfully: id: {}",
-                id
-            ))])),
+            Ok(id) => Ok(self.recorded_result(id, record_my_ledger_dto).await),
             Err(e) => Err(McpError::internal_error(e.to_string(), None)),
         }
     }
@@ -115,15 +214,297 @@ impl MCPHandler {
     }
 
     #[tool(
-        description = "Visualize your spending by category: today, this month, this year, or lifetime."
+        description = "Visualize your spending by category: today, this month, this year, or lifetime. Totals are signed, so income in a category offsets its spending, see spending_visualizer_v2 to keep them apart. Set output to Text for a Unicode bar chart of expenses with amounts and percentages."
     )]
     pub async fn spending_visualizer(
+        &self,
+        Parameters(spending_visualizer_model): Parameters<SpendingVisualizerModel>,
+    ) -> Result<CallToolResult, McpError> {
+        let SpendingVisualizerModel { filter, output } = spending_visualizer_model;
+        let title = format!("Spending by category: {}", range_title(&filter));
+
+        match output {
+            // The chart only shows spending, income would look like the largest expense
+            OutputFormat::Text => match self
+                .spending_scanner_use_case
+                .breakdown(SpendingScannerFilter { filter })
+                .await
+            {
+                Ok(breakdown) => Ok(CallToolResult::success(vec![Content::text(
+                    chart_renderer::text::category_table(&title, &breakdown),
+                )])),
+                Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+            },
+            OutputFormat::Json => match self
+                .spending_scanner_use_case
+                .visualize(SpendingScannerFilter { filter })
+                .await
+            {
+                Ok(results) => {
+                    if let Ok(res_json) = Content::json(results) {
+                        Ok(CallToolResult::success(vec![res_json]))
+                    } else {
+                        Err(McpError::internal_error(
+                            "Failed to convert results to JSON".to_string(),
+                            None,
+                        ))
+                    }
+                }
+                Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+            },
+        }
+    }
+
+    #[tool(
+        description = "Visualize income and expenses by category, with totals, net, and each category's share of spending: today, this month, this year, or lifetime. Set output to Text for a Unicode bar chart."
+    )]
+    pub async fn spending_visualizer_v2(
+        &self,
+        Parameters(spending_visualizer_model): Parameters<SpendingVisualizerModel>,
+    ) -> Result<CallToolResult, McpError> {
+        let SpendingVisualizerModel { filter, output } = spending_visualizer_model;
+        let title = format!("Spending by category: {}", range_title(&filter));
+
+        match self
+            .spending_scanner_use_case
+            .breakdown(SpendingScannerFilter { filter })
+            .await
+        {
+            Ok(results) => match output {
+                OutputFormat::Text => Ok(CallToolResult::success(vec![Content::text(
+                    chart_renderer::text::breakdown_table(&title, &results),
+                )])),
+                OutputFormat::Json => {
+                    if let Ok(res_json) = Content::json(results) {
+                        Ok(CallToolResult::success(vec![res_json]))
+                    } else {
+                        Err(McpError::internal_error(
+                            "Failed to convert results to JSON".to_string(),
+                            None,
+                        ))
+                    }
+                }
+            },
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "Draw your spending as a pie, bar, or line chart image. By category, or over time when group_by (Day, Week, Month, Year) is given. Only expenses are drawn, income is left out."
+    )]
+    pub async fn spending_chart(
+        &self,
+        Parameters(spending_chart_model): Parameters<SpendingChartModel>,
+    ) -> Result<CallToolResult, McpError> {
+        let SpendingChartModel {
+            filter,
+            kind,
+            group_by,
+            format,
+        } = spending_chart_model;
+
+        let svg = match group_by {
+            None => {
+                let title = format!("Spending by category: {}", range_title(&filter));
+
+                match self
+                    .spending_scanner_use_case
+                    .breakdown(SpendingScannerFilter { filter })
+                    .await
+                {
+                    Ok(breakdown) => chart_renderer::category_chart(kind, &title, &breakdown),
+                    Err(e) => return Err(McpError::internal_error(e.to_string(), None)),
+                }
+            }
+            Some(group_by) => {
+                let title = format!(
+                    "Spending by {}: {}",
+                    grouping_title(group_by),
+                    range_title(&filter)
+                );
+
+                match self
+                    .spending_scanner_use_case
+                    .time_series(SpendingTimeSeriesFilter { filter, group_by })
+                    .await
+                {
+                    Ok(buckets) => chart_renderer::time_series_chart(kind, &title, &buckets),
+                    Err(e) => return Err(McpError::internal_error(e.to_string(), None)),
+                }
+            }
+        };
+
+        match format {
+            ChartFormat::Svg => Ok(CallToolResult::success(vec![Content::image(
+                BASE64.encode(svg),
+                "image/svg+xml",
+            )])),
+            ChartFormat::Png => match chart_renderer::to_png(&svg) {
+                Ok(png) => Ok(CallToolResult::success(vec![Content::image(
+                    BASE64.encode(png),
+                    "image/png",
+                )])),
+                Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+            },
+        }
+    }
+
+    #[tool(
+        description = "Spending statistics per category (count, mean, median, p90, min, max, standard deviation): today, this month, this year, or lifetime."
+    )]
+    pub async fn spending_statistics(
         &self,
         Parameters(spending_scanner_filter): Parameters<SpendingScannerFilter>,
     ) -> Result<CallToolResult, McpError> {
         match self
             .spending_scanner_use_case
-            .visualize(spending_scanner_filter)
+            .statistics(spending_scanner_filter)
+            .await
+        {
+            Ok(results) => {
+                if let Ok(res_json) = Content::json(results) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "See your spending over time, grouped by day, week, month, or year with per-category totals for each bucket. Set output to Text for a Unicode bar chart and per-category sparklines of expenses."
+    )]
+    pub async fn spending_time_series(
+        &self,
+        Parameters(spending_time_series_model): Parameters<SpendingTimeSeriesModel>,
+    ) -> Result<CallToolResult, McpError> {
+        let SpendingTimeSeriesModel {
+            filter,
+            group_by,
+            output,
+        } = spending_time_series_model;
+        let title = format!(
+            "Spending by {}: {}",
+            grouping_title(group_by),
+            range_title(&filter)
+        );
+
+        match self
+            .spending_scanner_use_case
+            .time_series(SpendingTimeSeriesFilter { filter, group_by })
+            .await
+        {
+            Ok(results) => match output {
+                OutputFormat::Text => Ok(CallToolResult::success(vec![Content::text(
+                    chart_renderer::text::time_series_table(&title, &results),
+                )])),
+                OutputFormat::Json => {
+                    if let Ok(res_json) = Content::json(results) {
+                        Ok(CallToolResult::success(vec![res_json]))
+                    } else {
+                        Err(McpError::internal_error(
+                            "Failed to convert results to JSON".to_string(),
+                            None,
+                        ))
+                    }
+                }
+            },
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "Compare spending by category between two periods, or against the equivalent previous period when only the current one is given. Spending is positive, income is reported separately."
+    )]
+    pub async fn compare_spending(
+        &self,
+        Parameters(compare_spending_model): Parameters<CompareSpendingModel>,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .spending_scanner_use_case
+            .compare(compare_spending_model)
+            .await
+        {
+            Ok(result) => {
+                if let Ok(res_json) = Content::json(result) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "See the largest expenses in a date range and the merchants you spend on most often or most heavily."
+    )]
+    pub async fn top_spending(
+        &self,
+        Parameters(top_spending_filter): Parameters<TopSpendingFilter>,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .spending_scanner_use_case
+            .top_spending(top_spending_filter)
+            .await
+        {
+            Ok(result) => {
+                if let Ok(res_json) = Content::json(result) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "Find unusual spending in a date range: single expenses and category totals that are far above your usual spending. A single day is compared with the same weekday, longer ranges with past months scaled to the same length."
+    )]
+    pub async fn unusual_spending(
+        &self,
+        Parameters(unusual_spending_filter): Parameters<UnusualSpendingFilter>,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .unusual_spending_use_case
+            .detect(unusual_spending_filter)
+            .await
+        {
+            Ok(result) => {
+                if let Ok(res_json) = Content::json(result) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "Search transactions by description, e.g. \"when did I last buy shoes?\". Returns ranked matches with highlighted snippets, optionally filtered by date range and category."
+    )]
+    pub async fn search_transactions(
+        &self,
+        Parameters(search_transactions_model): Parameters<SearchTransactionsModel>,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .spending_scanner_use_case
+            .search_transactions(search_transactions_model)
             .await
         {
             Ok(results) => {
@@ -161,7 +542,9 @@ impl MCPHandler {
         }
     }
 
-    #[tool(description = "Add monthly spending into the list (due_date format: DD)")]
+    #[tool(
+        description = "Add scheduled spending into the list. Give a recurrence (Daily, Weekly, Monthly or Yearly every interval from anchor_date, with an optional end_date or count), or just due_date (format: DD, or \"last\" for the last day of each month) for monthly. Invalid input is rejected with every problem listed per field."
+    )]
     pub async fn add_monthly_spending(
         &self,
         Parameters(add_monthly_spending_model): Parameters<AddMonthlySpendingModel>,
@@ -175,6 +558,241 @@ impl MCPHandler {
                 "New monthly spending list added successfully: id: {}",
                 id
             ))])),
+            Err(e) => Err(validation_or_internal_error(e)),
+        }
+    }
+
+    #[tool(
+        description = "Update a scheduled spending item, only the given fields change. A new recurrence or due_date (format: DD, or \"last\" for the last day of each month) replaces the schedule. Invalid input is rejected with every problem listed per field."
+    )]
+    pub async fn update_monthly_spending(
+        &self,
+        Parameters(update_monthly_spending_model): Parameters<UpdateMonthlySpendingModel>,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .spending_scanner_use_case
+            .update_monthly_spending(update_monthly_spending_model)
+            .await
+        {
+            Ok(results) => {
+                if let Ok(res_json) = Content::json(results) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
+            Err(e) => Err(validation_or_internal_error(e)),
+        }
+    }
+
+    #[tool(
+        description = "List every scheduled payment from the monthly spending list that falls in a date range: today, this month, this year, or a custom range."
+    )]
+    pub async fn monthly_spending_occurrences(
+        &self,
+        Parameters(spending_scanner_filter): Parameters<SpendingScannerFilter>,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .spending_scanner_use_case
+            .monthly_spending_occurrences(spending_scanner_filter)
+            .await
+        {
+            Ok(results) => {
+                if let Ok(res_json) = Content::json(results) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "Post scheduled payments that are due into the ledger. Automatic items are posted right away, confirm-first items are listed as awaiting confirmation until their occurrence ids are passed in `confirm`. Already posted occurrences are never posted again."
+    )]
+    pub async fn post_due_bills(
+        &self,
+        Parameters(post_due_bills_model): Parameters<PostDueBillsModel>,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .bills_use_case
+            .post_due_bills(post_due_bills_model)
+            .await
+        {
+            Ok(results) => {
+                if let Ok(res_json) = Content::json(results) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "Mark a cycle of a monthly spending item as paid, or skipped. Defaults to the oldest unpaid cycle and today. Links the given ledger transaction, or records the payment in the ledger when none is given."
+    )]
+    pub async fn mark_bill_paid(
+        &self,
+        Parameters(mark_bill_paid_model): Parameters<MarkBillPaidModel>,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .bills_use_case
+            .mark_bill_paid(mark_bill_paid_model)
+            .await
+        {
+            Ok(results) => {
+                if let Ok(res_json) = Content::json(results) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "List unpaid cycles of monthly spending due by the end of this month, with which ones are overdue and by how many days"
+    )]
+    pub async fn unpaid_bills(&self) -> Result<CallToolResult, McpError> {
+        match self.bills_use_case.unpaid_bills().await {
+            Ok(results) => {
+                if let Ok(res_json) = Content::json(results) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "Forecast every scheduled payment in the next N days (default: 30, at most 366), in date order with a running total"
+    )]
+    pub async fn upcoming_bills(
+        &self,
+        Parameters(upcoming_bills_filter): Parameters<UpcomingBillsFilter>,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .bills_use_case
+            .upcoming_bills(upcoming_bills_filter)
+            .await
+        {
+            Ok(results) => {
+                if let Ok(res_json) = Content::json(results) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "Export the monthly spending list as an iCalendar (.ics) file in the server's export directory (EXPORT_DIR, default: exports), one recurring event with a reminder per item. Re-exporting updates the same events when imported again."
+    )]
+    pub async fn export_bills_calendar(
+        &self,
+        Parameters(export_bills_calendar_model): Parameters<ExportBillsCalendarModel>,
+    ) -> Result<CallToolResult, McpError> {
+        let items = match self
+            .spending_scanner_use_case
+            .view_all_monthly_spending_list()
+            .await
+        {
+            Ok(items) => items,
+            Err(e) => return Err(McpError::internal_error(e.to_string(), None)),
+        };
+
+        let path = export_bills_calendar_model
+            .path
+            .unwrap_or_else(|| DEFAULT_CALENDAR_PATH.to_string());
+        let exported = calendar_exporter::bills_calendar(
+            &items,
+            export_bills_calendar_model
+                .reminder_days
+                .unwrap_or(DEFAULT_REMINDER_DAYS),
+            Utc::now(),
+        )
+        .and_then(|calendar| calendar_exporter::write(&self.export_dir, &path, &calendar));
+
+        match exported {
+            Ok(path) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Export bills calendar successfully: {} events written to {}",
+                items.len(),
+                path.display()
+            ))])),
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "Detect suspected subscriptions in the ledger: expenses from the same merchant repeating at a regular cadence for a similar amount. Reports cadence, last charge, annual cost, and whether the monthly spending list already covers it. Untracked active ones come with a suggestion ready for add_monthly_spending."
+    )]
+    pub async fn detect_subscriptions(
+        &self,
+        Parameters(subscription_filter): Parameters<SubscriptionFilter>,
+    ) -> Result<CallToolResult, McpError> {
+        match self.subscription_use_case.detect(subscription_filter).await {
+            Ok(results) => {
+                if let Ok(res_json) = Content::json(results) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "Split spending in a date range into fixed costs (matching a scheduled monthly spending item or a subscription detected in the ledger) and variable costs, with category detail and each part's share of spending and of income. The range can be today, this month, this year, or a custom range."
+    )]
+    pub async fn fixed_vs_variable_costs(
+        &self,
+        Parameters(spending_scanner_filter): Parameters<SpendingScannerFilter>,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .subscription_use_case
+            .fixed_vs_variable(spending_scanner_filter)
+            .await
+        {
+            Ok(results) => {
+                if let Ok(res_json) = Content::json(results) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
             Err(e) => Err(McpError::internal_error(e.to_string(), None)),
         }
     }
@@ -196,6 +814,132 @@ impl MCPHandler {
         }
     }
 
+    #[tool(
+        description = "Set the monthly spending limit for a category, optionally only for an effective period (format: YYYY-MM). Setting the same category and period again replaces the limit. rollover carries unspent (CarrySurplus) or also overspent (CarryBoth) budget into the next month, up to rollover_cap."
+    )]
+    pub async fn set_budget(
+        &self,
+        Parameters(set_budget_model): Parameters<SetBudgetModel>,
+    ) -> Result<CallToolResult, McpError> {
+        match self.budget_use_case.set_budget(set_budget_model).await {
+            Ok(id) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Set budget successfully: id: {}",
+                id
+            ))])),
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(description = "View all category budgets")]
+    pub async fn list_budgets(&self) -> Result<CallToolResult, McpError> {
+        match self.budget_use_case.list_budgets().await {
+            Ok(results) => {
+                if let Ok(res_json) = Content::json(results) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "Check spending against category budgets for the current or a chosen month (format: YYYY-MM): amount carried from previous months, spent, remaining, percent used, and projected end-of-month spending."
+    )]
+    pub async fn budget_status(
+        &self,
+        Parameters(budget_status_filter): Parameters<BudgetStatusFilter>,
+    ) -> Result<CallToolResult, McpError> {
+        match self.budget_use_case.status(budget_status_filter).await {
+            Ok(results) => {
+                if let Ok(res_json) = Content::json(results) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "Assign unassigned income to an envelope. Envelopes are named after the category whose expenses they pay for, from the day money is first assigned to them."
+    )]
+    pub async fn assign_envelope(
+        &self,
+        Parameters(assign_envelope_model): Parameters<AssignEnvelopeModel>,
+    ) -> Result<CallToolResult, McpError> {
+        match self.envelope_use_case.assign(assign_envelope_model).await {
+            Ok(id) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Assign envelope successfully: id: {}",
+                id
+            ))])),
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(description = "Return available money from an envelope to the unassigned pool")]
+    pub async fn unassign_envelope(
+        &self,
+        Parameters(unassign_envelope_model): Parameters<AssignEnvelopeModel>,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .envelope_use_case
+            .unassign(unassign_envelope_model)
+            .await
+        {
+            Ok(id) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Unassign envelope successfully: id: {}",
+                id
+            ))])),
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(description = "Move available money from one envelope to another")]
+    pub async fn move_envelope(
+        &self,
+        Parameters(move_envelope_model): Parameters<MoveEnvelopeModel>,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .envelope_use_case
+            .move_between(move_envelope_model)
+            .await
+        {
+            Ok(id) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Move envelope successfully: id: {}",
+                id
+            ))])),
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "View the unassigned income pool and each envelope's assigned, spent, and available balance"
+    )]
+    pub async fn envelope_status(&self) -> Result<CallToolResult, McpError> {
+        match self.envelope_use_case.status().await {
+            Ok(results) => {
+                if let Ok(res_json) = Content::json(results) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
     #[tool(description = "View all tax deductions list")]
     pub async fn view_all_tax_deductions_list(&self) -> Result<CallToolResult, McpError> {
         match self
@@ -252,7 +996,35 @@ impl MCPHandler {
         }
     }
 
-    #[tool(description = "Calculate, simulate tax for a given year")]
+    #[tool(
+        description = "View all income entries of a given year with their income type and section 40(1)-40(8), untyped income counts as salary"
+    )]
+    pub async fn view_all_income_by_year(
+        &self,
+        Parameters(income_by_year_filter): Parameters<IncomeByYearFilter>,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .tax_simulator_use_case
+            .view_all_income_by_year(income_by_year_filter)
+            .await
+        {
+            Ok(results) => {
+                if let Ok(res_json) = Content::json(results) {
+                    Ok(CallToolResult::success(vec![res_json]))
+                } else {
+                    Err(McpError::internal_error(
+                        "Failed to convert results to JSON".to_string(),
+                        None,
+                    ))
+                }
+            }
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
+    #[tool(
+        description = "Calculate, simulate tax for a given year, showing each stage: gross income, expense deduction per income type, allowances, net taxable income and the progressive brackets"
+    )]
     pub async fn simulate_tax(
         &self,
         Parameters(tax_simulate_request_model): Parameters<TaxSimulateRequestModel>,
@@ -277,6 +1049,33 @@ impl MCPHandler {
     }
 }
 
+/// Validation errors are the caller's to fix, they come back as invalid params with the fields.
+fn validation_or_internal_error(e: anyhow::Error) -> McpError {
+    match e.downcast_ref::<ValidationErrors>() {
+        Some(errors) => McpError::invalid_params(e.to_string(), Some(json!(errors))),
+        None => McpError::internal_error(e.to_string(), None),
+    }
+}
+
+fn range_title(range: &Range) -> String {
+    match range {
+        Range::Today => "today".to_string(),
+        Range::ThisMonth => "this month".to_string(),
+        Range::ThisYear => "this year".to_string(),
+        Range::Lifetime => "lifetime".to_string(),
+        Range::Custom { start, end } => format!("{} to {}", start, end),
+    }
+}
+
+fn grouping_title(grouping: Grouping) -> &'static str {
+    match grouping {
+        Grouping::Day => "day",
+        Grouping::Week => "week",
+        Grouping::Month => "month",
+        Grouping::Year => "year",
+    }
+}
+
 #[prompt_router]
 impl MCPHandler {
     /// This is an example prompt that takes one required argument, message
@@ -1,2 +1,4 @@
+pub mod calendar_exporter;
+pub mod chart_renderer;
 pub mod database;
 pub mod mcp_handler;
@@ -5,15 +5,18 @@ use rmcp::{ServiceExt, transport::stdio};
 use tracing_subscriber::{self, EnvFilter};
 use your_money_left_the_chat::{
     application::use_cases::{
-        cash_flow::CashFlowUseCase, spending_scanner::SpendingScannerUseCase,
-        tax_simulator::TaxSimulatorUseCase,
+        bills::BillsUseCase, budget::BudgetUseCase, cash_flow::CashFlowUseCase,
+        envelope::EnvelopeUseCase, spending_scanner::SpendingScannerUseCase,
+        subscription::SubscriptionUseCase, tax_simulator::TaxSimulatorUseCase,
+        unusual_spending::UnusualSpendingUseCase,
     },
     config,
     infrastructure::{
         database::{
             conn,
             repositories::{
-                cash_flow::CashFlowSqlite, spending_scanner::SpendingScannerSqlite,
+                bills::BillSqlite, budget::BudgetSqlite, cash_flow::CashFlowSqlite,
+                envelope::EnvelopeSqlite, spending_scanner::SpendingScannerSqlite,
                 tax_simulator::TaxSimulatorSqlite,
             },
         },
@@ -41,11 +44,52 @@ async fn main() -> Result<()> {
         CashFlowUseCase::new(Arc::new(cash_flow_repository))
     };
 
-    let spending_scanner_use_case = {
-        let spending_scanner_repository = SpendingScannerSqlite::new(Arc::clone(&db_pool_artifact));
-        SpendingScannerUseCase::new(Arc::new(spending_scanner_repository))
+    let spending_scanner_repository =
+        Arc::new(SpendingScannerSqlite::new(Arc::clone(&db_pool_artifact)));
+
+    let spending_scanner_use_case =
+        SpendingScannerUseCase::new(Arc::clone(&spending_scanner_repository) as _);
+
+    let unusual_spending_use_case =
+        UnusualSpendingUseCase::new(Arc::clone(&spending_scanner_repository) as _);
+
+    let subscription_use_case =
+        SubscriptionUseCase::new(Arc::clone(&spending_scanner_repository) as _);
+
+    let budget_use_case = {
+        let budget_repository = BudgetSqlite::new(Arc::clone(&db_pool_artifact));
+        BudgetUseCase::new(
+            Arc::new(budget_repository),
+            Arc::clone(&spending_scanner_repository) as _,
+        )
     };
 
+    let envelope_use_case = {
+        let envelope_repository = EnvelopeSqlite::new(Arc::clone(&db_pool_artifact));
+        EnvelopeUseCase::new(
+            Arc::new(envelope_repository),
+            Arc::clone(&spending_scanner_repository) as _,
+        )
+    };
+
+    let bills_use_case = {
+        let bill_repository = BillSqlite::new(Arc::clone(&db_pool_artifact));
+        BillsUseCase::new(
+            Arc::new(bill_repository),
+            Arc::clone(&spending_scanner_repository) as _,
+        )
+    };
+
+    // Catch up on automatic bills that fell due while the server was not running
+    match bills_use_case.post_due_bills(Default::default()).await {
+        Ok(result) => tracing::info!(
+            "Posted {} due bills, {} awaiting confirmation",
+            result.posted.len(),
+            result.awaiting_confirmation.len()
+        ),
+        Err(e) => tracing::warn!("posting due bills failed: {:?}", e),
+    }
+
     let tax_simulator_use_case = {
         let tax_simulator_repository = TaxSimulatorSqlite::new(Arc::clone(&db_pool_artifact));
         TaxSimulatorUseCase::new(Arc::new(tax_simulator_repository))
@@ -55,6 +99,12 @@ async fn main() -> Result<()> {
         Arc::new(cash_flow_use_case),
         Arc::new(spending_scanner_use_case),
         Arc::new(tax_simulator_use_case),
+        Arc::new(unusual_spending_use_case),
+        Arc::new(budget_use_case),
+        Arc::new(envelope_use_case),
+        Arc::new(bills_use_case),
+        Arc::new(subscription_use_case),
+        config.export_dir,
     )
     .serve(stdio())
     .await